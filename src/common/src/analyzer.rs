@@ -0,0 +1,197 @@
+use crate::config::SessionConfig;
+use crate::error::{Error, Result};
+use crate::expr::{BinaryExpr, BinaryOperator, Cast, Expr};
+use crate::plan::LogicalPlan;
+use crate::scalar::ScalarValue;
+use crate::schema::DataType;
+use crate::tree_node::{Transformed, TreeNode};
+
+/// A rewrite the analyzer applies once to a whole plan. Unlike optimizer
+/// rules, which only ever make a plan cheaper, an analyzer rule rejects
+/// plans that can't be run.
+pub trait AnalyzerRule {
+    fn name(&self) -> &str;
+
+    fn analyze(&self, plan: &LogicalPlan) -> Result<LogicalPlan>;
+}
+
+/// Rewrites a plan needs before it can be optimized, run in order.
+pub struct Analyzer {
+    pub rules: Vec<Box<dyn AnalyzerRule>>,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self::with_config(SessionConfig::default())
+    }
+}
+
+impl Analyzer {
+    /// An analyzer running the default rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An analyzer running the default rules, resolving identifiers as
+    /// configured for the session.
+    pub fn with_config(config: SessionConfig) -> Self {
+        Self::with_rules(vec![
+            Box::new(QualifyColumns { config }),
+            Box::new(TypeCoercion),
+        ])
+    }
+
+    pub fn with_rules(rules: Vec<Box<dyn AnalyzerRule>>) -> Self {
+        Self { rules }
+    }
+
+    /// Analyze `plan`, returning a plan the optimizer can work with or the
+    /// reason it is invalid. Analysis stops at the first rule that fails.
+    pub fn analyze(&self, plan: &LogicalPlan) -> Result<LogicalPlan> {
+        let mut plan = plan.clone();
+        for rule in &self.rules {
+            plan = rule.analyze(&plan)?;
+        }
+        plan.validate()?;
+        Ok(plan)
+    }
+}
+
+/// Replaces each column reference with the qualified column of the field it
+/// resolves to, spelled as in the schema.
+pub struct QualifyColumns {
+    pub config: SessionConfig,
+}
+
+impl AnalyzerRule for QualifyColumns {
+    fn name(&self) -> &str {
+        "qualify_columns"
+    }
+
+    fn analyze(&self, plan: &LogicalPlan) -> Result<LogicalPlan> {
+        Ok(plan
+            .transform(|node| qualify_columns(node, &self.config))?
+            .data)
+    }
+}
+
+/// Casts the operands of comparisons and numeric arithmetic to their common
+/// type, failing for operands that can't be compared. An untyped NULL
+/// operand takes the type of the other operand.
+pub struct TypeCoercion;
+
+impl AnalyzerRule for TypeCoercion {
+    fn name(&self) -> &str {
+        "type_coercion"
+    }
+
+    fn analyze(&self, plan: &LogicalPlan) -> Result<LogicalPlan> {
+        Ok(plan.transform(coerce_types)?.data)
+    }
+}
+
+fn qualify_columns(plan: &LogicalPlan, config: &SessionConfig) -> Result<Transformed<LogicalPlan>> {
+    plan.rewrite_expressions(|expr, schema| {
+        expr.transform(|expr| {
+            let Expr::Column(column) = expr else {
+                return Ok(Transformed::no(expr.clone()));
+            };
+            let qualified = schema.qualified_field(column, config)?.column();
+            if qualified.relation == column.relation && qualified.name == column.name {
+                return Ok(Transformed::no(expr.clone()));
+            }
+            Ok(Transformed::yes(Expr::Column(qualified)))
+        })
+    })
+}
+
+fn coerce_types(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+    plan.rewrite_expressions(|expr, schema| {
+        expr.transform(|expr| {
+            let Expr::BinaryExpr(binary) = expr else {
+                return Ok(Transformed::no(expr.clone()));
+            };
+            let BinaryExpr { left, op, right } = binary;
+            let (left_type, right_type) = (left.get_type(schema)?, right.get_type(schema)?);
+            if left_type == right_type {
+                return Ok(Transformed::no(expr.clone()));
+            }
+            if left_type == DataType::Null || right_type == DataType::Null {
+                return Ok(type_null_operand(binary, left_type, right_type));
+            }
+            let data_type = match op {
+                BinaryOperator::And | BinaryOperator::Or => None,
+                // Dates and decimals have their own arithmetic
+                BinaryOperator::Plus
+                | BinaryOperator::Minus
+                | BinaryOperator::Multiply
+                | BinaryOperator::Divide => (left_type.is_numeric() && right_type.is_numeric())
+                    .then(|| left_type.common_supertype(&right_type))
+                    .flatten(),
+                _ => Some(left_type.common_supertype(&right_type).ok_or_else(|| {
+                    Error::Plan(format!("cannot compare {left_type} with {right_type}"))
+                })?),
+            };
+            let Some(data_type) = data_type else {
+                return Ok(Transformed::no(expr.clone()));
+            };
+            let cast = |operand: &Expr, operand_type: DataType| {
+                if operand_type == data_type {
+                    operand.clone()
+                } else {
+                    Expr::Cast(Cast {
+                        expr: Box::new(operand.clone()),
+                        data_type,
+                    })
+                }
+            };
+            Ok(Transformed::yes(Expr::BinaryExpr(BinaryExpr {
+                left: Box::new(cast(left, left_type)),
+                op: *op,
+                right: Box::new(cast(right, right_type)),
+            })))
+        })
+    })
+}
+
+/// Give the untyped NULL operand of a binary expression the type of the
+/// other operand, where the operator accepts two operands of that type.
+fn type_null_operand(
+    binary: &BinaryExpr,
+    left_type: DataType,
+    right_type: DataType,
+) -> Transformed<Expr> {
+    let BinaryExpr { left, op, right } = binary;
+    let data_type = if left_type == DataType::Null {
+        right_type
+    } else {
+        left_type
+    };
+    let applies = match op {
+        BinaryOperator::And | BinaryOperator::Or => data_type == DataType::Boolean,
+        // NULL days added to a date stay a number of days
+        BinaryOperator::Plus
+        | BinaryOperator::Minus
+        | BinaryOperator::Multiply
+        | BinaryOperator::Divide => {
+            data_type.is_numeric() || matches!(data_type, DataType::Decimal128 { .. })
+        }
+        _ => true,
+    };
+    let typed = |operand: &Expr, operand_type: DataType| match operand {
+        _ if operand_type != DataType::Null => operand.clone(),
+        Expr::Literal(_) => Expr::Literal(ScalarValue::new_null(&data_type)),
+        _ => Expr::Cast(Cast {
+            expr: Box::new(operand.clone()),
+            data_type,
+        }),
+    };
+    if !applies {
+        return Transformed::no(Expr::BinaryExpr(binary.clone()));
+    }
+    Transformed::yes(Expr::BinaryExpr(BinaryExpr {
+        left: Box::new(typed(left, left_type)),
+        op: *op,
+        right: Box::new(typed(right, right_type)),
+    }))
+}
@@ -1,6 +1,35 @@
+use std::fmt;
+
+use crate::spans::Spans;
+use crate::table_reference::TableReference;
+
 /// A named reference to a qualified field in a schema.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Column {
     pub relation: Option<TableReference>,
     pub name: String,
     pub spans: Spans,
 }
+
+impl Column {
+    pub fn new(relation: Option<TableReference>, name: impl Into<String>) -> Self {
+        Self {
+            relation,
+            name: name.into(),
+            spans: Spans::default(),
+        }
+    }
+
+    pub fn new_unqualified(name: impl Into<String>) -> Self {
+        Self::new(None, name)
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.relation {
+            Some(relation) => write!(f, "{relation}.{}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+/// Options that control how queries are planned within a session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionConfig {
+    /// Whether identifiers that differ only in case refer to different
+    /// columns. Defaults to false, treating identifiers as if folded to
+    /// lowercase like standard SQL.
+    pub case_sensitive_identifiers: bool,
+}
+
+impl SessionConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_case_sensitive_identifiers(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive_identifiers = case_sensitive;
+        self
+    }
+
+    /// Whether two identifiers are the same under this configuration.
+    pub fn identifiers_eq(&self, left: &str, right: &str) -> bool {
+        if self.case_sensitive_identifiers {
+            left == right
+        } else {
+            left.eq_ignore_ascii_case(right)
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt;
+use std::io;
+use std::num::ParseIntError;
+
+/// Errors raised while building, validating or optimizing a plan.
+#[derive(Debug)]
+pub enum Error {
+    /// The plan is invalid, e.g. an operator is applied to unsupported types.
+    Plan(String),
+    /// A column could not be resolved against a schema.
+    SchemaError(String),
+    /// An expression could not be evaluated, e.g. on arithmetic overflow.
+    Execution(String),
+    /// An invariant was violated; this indicates a bug.
+    Internal(String),
+    /// Reading or writing data failed.
+    IoError(io::Error),
+    /// A value could not be parsed as an integer.
+    ParseIntError(ParseIntError),
+    /// An error raised at a node of a plan, along with the child-index path
+    /// from the root to that node.
+    AtNode {
+        node: String,
+        path: Vec<usize>,
+        error: Box<Error>,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors that wrap another error only describe themselves: the wrapped
+/// error is their [`source`](std::error::Error::source), so reporters that
+/// walk the chain show each message once. Node context is the exception, as
+/// it means nothing on its own: it is shown after the error it was attached
+/// to, e.g. "Error during planning: ... at Filter (root/0/1)", and its
+/// source is that error's source.
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Plan(msg) => write!(f, "Error during planning: {msg}"),
+            Error::SchemaError(msg) => write!(f, "Schema error: {msg}"),
+            Error::Execution(msg) => write!(f, "Execution error: {msg}"),
+            Error::Internal(msg) => write!(f, "Internal error: {msg}"),
+            Error::IoError(_) => write!(f, "IO error"),
+            Error::ParseIntError(_) => write!(f, "Parse error"),
+            Error::AtNode { node, path, error } => {
+                write!(f, "{error} at {node} (root")?;
+                for index in path {
+                    write!(f, "/{index}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IoError(error) => Some(error),
+            Error::ParseIntError(error) => Some(error),
+            Error::AtNode { error, .. } => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::IoError(error)
+    }
+}
+
+impl From<ParseIntError> for Error {
+    fn from(error: ParseIntError) -> Self {
+        Error::ParseIntError(error)
+    }
+}
+
+impl Error {
+    /// Attach the plan node the error was raised at. An error that already
+    /// knows its node keeps it, since that node is the more precise one.
+    pub fn at_node(self, node: impl Into<String>, path: Vec<usize>) -> Self {
+        match self {
+            Error::AtNode { .. } => self,
+            error => Error::AtNode {
+                node: node.into(),
+                path,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// The child-index path to the node the error was raised at, if known.
+    pub fn path(&self) -> Option<&[usize]> {
+        match self {
+            Error::AtNode { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The error without any node context.
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::AtNode { error, .. } => error.inner(),
+            error => error,
+        }
+    }
+}
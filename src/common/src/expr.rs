@@ -1,4 +1,13 @@
+use std::fmt;
+
+use crate::column::Column;
+use crate::error::Result;
+use crate::scalar::ScalarValue;
+use crate::schema::DataType;
+use crate::tree_node::{Transformed, TreeNode, map_until_stop};
+
 /// Represents logical expressions such as `A + 1`
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     /// A named reference to a qualified field in a schema.
     Column(Column),
@@ -6,4 +15,305 @@ pub enum Expr {
     Literal(ScalarValue),
     /// A binary expression such as "age > 21".
     BinaryExpr(BinaryExpr),
+    /// CASE [expr] WHEN condition THEN result ... [ELSE result] END
+    Case(Case),
+    /// An expression with a name, e.g. `salary * 2 AS bonus`.
+    Alias(Alias),
+    /// Whether an expression is NULL.
+    IsNull(Box<Expr>),
+    /// Whether an expression is not NULL.
+    IsNotNull(Box<Expr>),
+    /// The first of its arguments that is not NULL.
+    Coalesce(Vec<Expr>),
+    /// Converts an expression to another type.
+    Cast(Cast),
+}
+
+/// Binary expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryExpr {
+    /// Left-hand side of the expression
+    pub left: Box<Expr>,
+    /// The comparison operator
+    pub op: BinaryOperator,
+    /// Right-hand side of the expression
+    pub right: Box<Expr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+}
+
+/// CASE expression. Without a base `expr` each WHEN is a boolean condition,
+/// otherwise it is compared against `expr`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    /// Optional base expression compared against each WHEN
+    pub expr: Option<Box<Expr>>,
+    /// The (WHEN, THEN) pairs, in order
+    pub when_then_expr: Vec<(Box<Expr>, Box<Expr>)>,
+    /// Result when no WHEN matches; NULL if absent
+    pub else_expr: Option<Box<Expr>>,
+}
+
+/// Cast expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cast {
+    /// The expression being cast
+    pub expr: Box<Expr>,
+    /// The type to cast to
+    pub data_type: DataType,
+}
+
+/// An aliased expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alias {
+    pub expr: Box<Expr>,
+    pub name: String,
+}
+
+impl Expr {
+    pub fn as_column(&self) -> Option<&Column> {
+        match self {
+            Expr::Column(column) => Some(column),
+            _ => None,
+        }
+    }
+
+    pub fn as_literal(&self) -> Option<&ScalarValue> {
+        match self {
+            Expr::Literal(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_binary(&self) -> Option<&BinaryExpr> {
+        match self {
+            Expr::BinaryExpr(binary_expr) => Some(binary_expr),
+            _ => None,
+        }
+    }
+
+    /// The column this expression is, or the expression itself if it isn't
+    /// a column.
+    pub fn try_into_column(self) -> std::result::Result<Column, Expr> {
+        match self {
+            Expr::Column(column) => Ok(column),
+            expr => Err(expr),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Column(column) => write!(f, "{column}"),
+            Expr::Literal(ScalarValue::Utf8(Some(value))) => write!(f, "'{value}'"),
+            Expr::Literal(value) => write!(f, "{value}"),
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                write_operand(f, left)?;
+                write!(f, " {op} ")?;
+                write_operand(f, right)
+            }
+            Expr::Case(Case {
+                expr,
+                when_then_expr,
+                else_expr,
+            }) => {
+                write!(f, "CASE")?;
+                if let Some(expr) = expr {
+                    write!(f, " {expr}")?;
+                }
+                for (when, then) in when_then_expr {
+                    write!(f, " WHEN {when} THEN {then}")?;
+                }
+                if let Some(else_expr) = else_expr {
+                    write!(f, " ELSE {else_expr}")?;
+                }
+                write!(f, " END")
+            }
+            Expr::Alias(Alias { expr, name }) => write!(f, "{expr} AS {name}"),
+            Expr::IsNull(expr) => {
+                write_operand(f, expr)?;
+                write!(f, " IS NULL")
+            }
+            Expr::IsNotNull(expr) => {
+                write_operand(f, expr)?;
+                write!(f, " IS NOT NULL")
+            }
+            Expr::Cast(Cast { expr, data_type }) => write!(f, "CAST({expr} AS {data_type})"),
+            Expr::Coalesce(args) => {
+                let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+                write!(f, "coalesce({})", args.join(", "))
+            }
+        }
+    }
+}
+
+/// Nested binary expressions are parenthesized so the rendering is
+/// unambiguous.
+fn write_operand(f: &mut fmt::Formatter<'_>, expr: &Expr) -> fmt::Result {
+    match expr {
+        Expr::BinaryExpr(_) => write!(f, "({expr})"),
+        _ => write!(f, "{expr}"),
+    }
+}
+
+impl BinaryExpr {
+    pub fn new(left: Box<Expr>, op: BinaryOperator, right: Box<Expr>) -> Self {
+        Self { left, op, right }
+    }
+
+    pub fn left(&self) -> &Expr {
+        &self.left
+    }
+
+    pub fn op(&self) -> BinaryOperator {
+        self.op
+    }
+
+    pub fn right(&self) -> &Expr {
+        &self.right
+    }
+
+    /// The equivalent expression with the operands the other way round, e.g.
+    /// `b > a` for `a < b`. `None` for `-` and `/`, which have no such form.
+    pub fn swap_operands(self) -> Option<Self> {
+        Some(Self {
+            left: self.right,
+            op: self.op.swap()?,
+            right: self.left,
+        })
+    }
+}
+
+impl BinaryOperator {
+    /// The operator that gives the same result with the operands swapped.
+    pub fn swap(&self) -> Option<BinaryOperator> {
+        match self {
+            BinaryOperator::Lt => Some(BinaryOperator::Gt),
+            BinaryOperator::Le => Some(BinaryOperator::Ge),
+            BinaryOperator::Gt => Some(BinaryOperator::Lt),
+            BinaryOperator::Ge => Some(BinaryOperator::Le),
+            BinaryOperator::Eq
+            | BinaryOperator::Ne
+            | BinaryOperator::And
+            | BinaryOperator::Or
+            | BinaryOperator::Plus
+            | BinaryOperator::Multiply => Some(*self),
+            BinaryOperator::Minus | BinaryOperator::Divide => None,
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            BinaryOperator::Eq => "=",
+            BinaryOperator::Ne => "!=",
+            BinaryOperator::Lt => "<",
+            BinaryOperator::Le => "<=",
+            BinaryOperator::Gt => ">",
+            BinaryOperator::Ge => ">=",
+            BinaryOperator::And => "AND",
+            BinaryOperator::Or => "OR",
+            BinaryOperator::Plus => "+",
+            BinaryOperator::Minus => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+        };
+        write!(f, "{op}")
+    }
+}
+
+impl TreeNode for Expr {
+    fn apply_children<F>(&self, f: F) -> Result<Transformed<Self>>
+    where
+        F: Fn(&Self) -> Result<Transformed<Self>>,
+    {
+        self.clone().map_children(|child| f(&child))
+    }
+
+    fn map_children<F>(self, f: F) -> Result<Transformed<Self>>
+    where
+        F: Fn(Self) -> Result<Transformed<Self>>,
+    {
+        match self {
+            // Leaf nodes - no children to transform
+            Expr::Column(_) | Expr::Literal(_) => Ok(Transformed::no(self)),
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                Ok(
+                    map_until_stop(vec![*left, *right], f)?.map_data(|children| {
+                        let [left, right]: [Expr; 2] = children
+                            .try_into()
+                            .expect("binary expression has two children");
+                        Expr::BinaryExpr(BinaryExpr {
+                            left: Box::new(left),
+                            op,
+                            right: Box::new(right),
+                        })
+                    }),
+                )
+            }
+            Expr::Alias(Alias { expr, name }) => Ok(f(*expr)?.map_data(|expr| {
+                Expr::Alias(Alias {
+                    expr: Box::new(expr),
+                    name,
+                })
+            })),
+            Expr::IsNull(expr) => Ok(f(*expr)?.map_data(|expr| Expr::IsNull(Box::new(expr)))),
+            Expr::IsNotNull(expr) => Ok(f(*expr)?.map_data(|expr| Expr::IsNotNull(Box::new(expr)))),
+            Expr::Coalesce(args) => Ok(map_until_stop(args, f)?.map_data(Expr::Coalesce)),
+            Expr::Cast(Cast { expr, data_type }) => Ok(f(*expr)?.map_data(|expr| {
+                Expr::Cast(Cast {
+                    expr: Box::new(expr),
+                    data_type,
+                })
+            })),
+            Expr::Case(Case {
+                expr,
+                when_then_expr,
+                else_expr,
+            }) => {
+                let has_expr = expr.is_some();
+                let has_else = else_expr.is_some();
+                let num_when_then = when_then_expr.len();
+
+                // Flatten the children in evaluation order
+                let mut children = Vec::with_capacity(num_when_then * 2 + 2);
+                children.extend(expr.map(|expr| *expr));
+                for (when, then) in when_then_expr {
+                    children.push(*when);
+                    children.push(*then);
+                }
+                children.extend(else_expr.map(|expr| *expr));
+
+                Ok(map_until_stop(children, f)?.map_data(|children| {
+                    let mut children = children.into_iter().map(Box::new);
+                    let expr = if has_expr { children.next() } else { None };
+                    let when_then_expr = (0..num_when_then)
+                        .filter_map(|_| Some((children.next()?, children.next()?)))
+                        .collect();
+                    let else_expr = if has_else { children.next() } else { None };
+                    Expr::Case(Case {
+                        expr,
+                        when_then_expr,
+                        else_expr,
+                    })
+                }))
+            }
+        }
+    }
 }
@@ -0,0 +1,120 @@
+use crate::error::{Error, Result};
+use crate::expr::{Alias, BinaryExpr, BinaryOperator, Case, Cast, Expr};
+use crate::expr_eval::{evaluate_binary, short_circuit};
+use crate::scalar::ScalarValue;
+use crate::schema::Schema;
+
+type Evaluator = Box<dyn Fn(&[ScalarValue]) -> Result<ScalarValue> + Send + Sync>;
+
+/// An expression resolved against a schema once, so that evaluating it
+/// doesn't look up columns by name or re-walk the expression per row.
+pub struct CompiledExpr {
+    evaluator: Evaluator,
+}
+
+impl CompiledExpr {
+    /// Evaluate against a row laid out as the schema it was compiled for.
+    pub fn evaluate(&self, row: &[ScalarValue]) -> Result<ScalarValue> {
+        (self.evaluator)(row)
+    }
+}
+
+/// Compile `expr` against `schema`. Evaluating the result gives the same
+/// value as [`Expr::evaluate`] with that schema.
+pub fn compile_expr(expr: &Expr, schema: &Schema) -> Result<CompiledExpr> {
+    Ok(CompiledExpr {
+        evaluator: compile(expr, schema)?,
+    })
+}
+
+fn compile(expr: &Expr, schema: &Schema) -> Result<Evaluator> {
+    Ok(match expr {
+        Expr::Column(column) => {
+            let index = schema.index_of_column(column)?;
+            let name = column.name.clone();
+            Box::new(move |row| {
+                row.get(index)
+                    .cloned()
+                    .ok_or_else(|| Error::Execution(format!("row has no value for column {name}")))
+            })
+        }
+        Expr::Literal(value) => {
+            let value = value.clone();
+            Box::new(move |_| Ok(value.clone()))
+        }
+        Expr::Alias(Alias { expr, .. }) => compile(expr, schema)?,
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let (left, op, right) = (compile(left, schema)?, *op, compile(right, schema)?);
+            Box::new(move |row| {
+                let left = left(row)?;
+                if let Some(value) = short_circuit(&left, op) {
+                    return Ok(value);
+                }
+                evaluate_binary(&left, op, &right(row)?)
+            })
+        }
+        Expr::Case(Case {
+            expr,
+            when_then_expr,
+            else_expr,
+        }) => {
+            let base = expr
+                .as_ref()
+                .map(|expr| compile(expr, schema))
+                .transpose()?;
+            let when_then = when_then_expr
+                .iter()
+                .map(|(when, then)| Ok((compile(when, schema)?, compile(then, schema)?)))
+                .collect::<Result<Vec<_>>>()?;
+            let else_expr = else_expr
+                .as_ref()
+                .map(|expr| compile(expr, schema))
+                .transpose()?;
+            Box::new(move |row| {
+                let base = base.as_ref().map(|base| base(row)).transpose()?;
+                for (when, then) in &when_then {
+                    let when = when(row)?;
+                    let matched = match &base {
+                        Some(base) => evaluate_binary(base, BinaryOperator::Eq, &when)?,
+                        None => when,
+                    };
+                    if matched == ScalarValue::Boolean(Some(true)) {
+                        return then(row);
+                    }
+                }
+                match &else_expr {
+                    Some(else_expr) => else_expr(row),
+                    None => Ok(ScalarValue::Null),
+                }
+            })
+        }
+        Expr::IsNull(expr) => {
+            let expr = compile(expr, schema)?;
+            Box::new(move |row| Ok(ScalarValue::Boolean(Some(expr(row)?.is_null()))))
+        }
+        Expr::IsNotNull(expr) => {
+            let expr = compile(expr, schema)?;
+            Box::new(move |row| Ok(ScalarValue::Boolean(Some(!expr(row)?.is_null()))))
+        }
+        Expr::Cast(Cast { expr, data_type }) => {
+            let (expr, data_type) = (compile(expr, schema)?, *data_type);
+            Box::new(move |row| expr(row)?.cast_to(&data_type))
+        }
+        Expr::Coalesce(args) => {
+            let args = args
+                .iter()
+                .map(|arg| compile(arg, schema))
+                .collect::<Result<Vec<_>>>()?;
+            Box::new(move |row| {
+                let mut value = ScalarValue::Null;
+                for arg in &args {
+                    value = arg(row)?;
+                    if !value.is_null() {
+                        break;
+                    }
+                }
+                Ok(value)
+            })
+        }
+    })
+}
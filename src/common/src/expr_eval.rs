@@ -0,0 +1,252 @@
+use std::cmp::Ordering;
+
+use crate::error::{Error, Result};
+use crate::expr::{Alias, BinaryExpr, BinaryOperator, Case, Cast, Expr};
+use crate::scalar::{ScalarValue, check_precision, rescale};
+use crate::schema::{DataType, Schema};
+
+impl Expr {
+    /// Evaluate this expression against a single row, whose values are laid
+    /// out in the order of `schema`'s fields.
+    pub fn evaluate(&self, schema: &Schema, row: &[ScalarValue]) -> Result<ScalarValue> {
+        match self {
+            Expr::Column(column) => {
+                let index = schema.index_of_column(column)?;
+                row.get(index).cloned().ok_or_else(|| {
+                    Error::Execution(format!("row has no value for column {}", column.name))
+                })
+            }
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Alias(Alias { expr, .. }) => expr.evaluate(schema, row),
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                let left = left.evaluate(schema, row)?;
+                if let Some(value) = short_circuit(&left, *op) {
+                    return Ok(value);
+                }
+                let right = right.evaluate(schema, row)?;
+                evaluate_binary(&left, *op, &right)
+            }
+            Expr::Case(Case {
+                expr,
+                when_then_expr,
+                else_expr,
+            }) => {
+                let base = expr
+                    .as_ref()
+                    .map(|expr| expr.evaluate(schema, row))
+                    .transpose()?;
+                for (when, then) in when_then_expr {
+                    let when = when.evaluate(schema, row)?;
+                    let matched = match &base {
+                        Some(base) => evaluate_binary(base, BinaryOperator::Eq, &when)?,
+                        None => when,
+                    };
+                    if matched == ScalarValue::Boolean(Some(true)) {
+                        return then.evaluate(schema, row);
+                    }
+                }
+                match else_expr {
+                    Some(else_expr) => else_expr.evaluate(schema, row),
+                    None => Ok(ScalarValue::Null),
+                }
+            }
+            Expr::IsNull(expr) => Ok(ScalarValue::Boolean(Some(
+                expr.evaluate(schema, row)?.is_null(),
+            ))),
+            Expr::IsNotNull(expr) => Ok(ScalarValue::Boolean(Some(
+                !expr.evaluate(schema, row)?.is_null(),
+            ))),
+            Expr::Cast(Cast { expr, data_type }) => expr.evaluate(schema, row)?.cast_to(data_type),
+            Expr::Coalesce(args) => {
+                let mut value = ScalarValue::Null;
+                for arg in args {
+                    value = arg.evaluate(schema, row)?;
+                    if !value.is_null() {
+                        break;
+                    }
+                }
+                Ok(value)
+            }
+        }
+    }
+}
+
+/// The result of `left op right` if `left` alone decides it: FALSE for
+/// `FALSE AND ...` and TRUE for `TRUE OR ...`, whatever the right operand
+/// would be, even NULL or an error.
+pub(crate) fn short_circuit(left: &ScalarValue, op: BinaryOperator) -> Option<ScalarValue> {
+    match (op, left) {
+        (BinaryOperator::And, ScalarValue::Boolean(Some(false)))
+        | (BinaryOperator::Or, ScalarValue::Boolean(Some(true))) => Some(left.clone()),
+        _ => None,
+    }
+}
+
+pub(crate) fn evaluate_binary(
+    left: &ScalarValue,
+    op: BinaryOperator,
+    right: &ScalarValue,
+) -> Result<ScalarValue> {
+    match op {
+        BinaryOperator::And | BinaryOperator::Or => {
+            let left = as_bool(left, op)?;
+            let right = as_bool(right, op)?;
+            // Three-valued logic: NULL only decides the result when the
+            // other side doesn't
+            let value = match op {
+                BinaryOperator::And => match (left, right) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                },
+                _ => match (left, right) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                },
+            };
+            Ok(ScalarValue::Boolean(value))
+        }
+        BinaryOperator::Plus
+        | BinaryOperator::Minus
+        | BinaryOperator::Multiply
+        | BinaryOperator::Divide => {
+            let data_type = op
+                .arithmetic_type(&left.data_type(), &right.data_type())
+                .ok_or_else(|| {
+                    Error::Execution(format!("cannot apply {op} to {left:?} and {right:?}"))
+                })?;
+            if left.is_null() || right.is_null() {
+                return Ok(ScalarValue::new_null(&data_type));
+            }
+            let overflow = || Error::Execution(format!("overflow computing {left} {op} {right}"));
+            match (left, right) {
+                (ScalarValue::Date32(Some(left)), ScalarValue::Date32(Some(right))) => {
+                    return Ok(ScalarValue::Int64(Some(*left as i64 - *right as i64)));
+                }
+                (ScalarValue::Date32(Some(date)), days)
+                | (days, ScalarValue::Date32(Some(date))) => {
+                    let ScalarValue::Int64(Some(days)) = days.cast_to(&DataType::Int64)? else {
+                        return Err(Error::Internal(format!("{days:?} is not a number of days")));
+                    };
+                    let date = *date as i64;
+                    let date = match op {
+                        BinaryOperator::Minus => date.checked_sub(days),
+                        _ => date.checked_add(days),
+                    };
+                    let date = date.and_then(|date| i32::try_from(date).ok());
+                    return Ok(ScalarValue::Date32(Some(date.ok_or_else(overflow)?)));
+                }
+                _ => {}
+            }
+            if let (
+                ScalarValue::Decimal128(Some(left), _, left_scale),
+                ScalarValue::Decimal128(Some(right), _, right_scale),
+                DataType::Decimal128 { precision, scale },
+            ) = (left, right, data_type)
+            {
+                let value = match op {
+                    BinaryOperator::Multiply => left.checked_mul(*right),
+                    _ => {
+                        let left = rescale(*left, *left_scale, scale).ok_or_else(overflow)?;
+                        let right = rescale(*right, *right_scale, scale).ok_or_else(overflow)?;
+                        match op {
+                            BinaryOperator::Plus => left.checked_add(right),
+                            _ => left.checked_sub(right),
+                        }
+                    }
+                };
+                let value = value.and_then(|value| check_precision(value, precision));
+                return Ok(ScalarValue::Decimal128(
+                    Some(value.ok_or_else(overflow)?),
+                    precision,
+                    scale,
+                ));
+            }
+            match (left.cast_to(&data_type)?, right.cast_to(&data_type)?) {
+                (ScalarValue::Int32(Some(left)), ScalarValue::Int32(Some(right))) => {
+                    let value = evaluate_integer(left as i64, op, right as i64)?;
+                    let value = value.and_then(|value| i32::try_from(value).ok());
+                    Ok(ScalarValue::Int32(Some(value.ok_or_else(overflow)?)))
+                }
+                (ScalarValue::Int64(Some(left)), ScalarValue::Int64(Some(right))) => {
+                    let value = evaluate_integer(left, op, right)?;
+                    Ok(ScalarValue::Int64(Some(value.ok_or_else(overflow)?)))
+                }
+                (ScalarValue::Float64(Some(left)), ScalarValue::Float64(Some(right))) => {
+                    let value = match op {
+                        BinaryOperator::Plus => left + right,
+                        BinaryOperator::Minus => left - right,
+                        BinaryOperator::Multiply => left * right,
+                        _ if right == 0.0 => {
+                            return Err(Error::Execution("division by zero".to_string()));
+                        }
+                        _ => left / right,
+                    };
+                    Ok(ScalarValue::Float64(Some(value)))
+                }
+                (left, right) => Err(Error::Internal(format!(
+                    "unexpected operands {left:?} and {right:?} for {op}"
+                ))),
+            }
+        }
+        _ => {
+            let Some(ordering) = compare(left, right)? else {
+                return Ok(ScalarValue::Boolean(None));
+            };
+            let value = match op {
+                BinaryOperator::Eq => ordering == Ordering::Equal,
+                BinaryOperator::Ne => ordering != Ordering::Equal,
+                BinaryOperator::Lt => ordering == Ordering::Less,
+                BinaryOperator::Le => ordering != Ordering::Greater,
+                BinaryOperator::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            };
+            Ok(ScalarValue::Boolean(Some(value)))
+        }
+    }
+}
+
+/// Integer arithmetic; `None` on overflow.
+fn evaluate_integer(left: i64, op: BinaryOperator, right: i64) -> Result<Option<i64>> {
+    Ok(match op {
+        BinaryOperator::Plus => left.checked_add(right),
+        BinaryOperator::Minus => left.checked_sub(right),
+        BinaryOperator::Multiply => left.checked_mul(right),
+        _ if right == 0 => return Err(Error::Execution("division by zero".to_string())),
+        _ => left.checked_div(right),
+    })
+}
+
+fn as_bool(value: &ScalarValue, op: BinaryOperator) -> Result<Option<bool>> {
+    match value {
+        ScalarValue::Boolean(value) => Ok(*value),
+        value if value.is_null() => Ok(None),
+        value => Err(Error::Execution(format!("cannot apply {op} to {value:?}"))),
+    }
+}
+
+/// Compare two values, converting numbers to a common type; `None` if
+/// either is NULL.
+fn compare(left: &ScalarValue, right: &ScalarValue) -> Result<Option<Ordering>> {
+    if left.is_null() || right.is_null() {
+        return Ok(None);
+    }
+    let incomparable = || Error::Execution(format!("cannot compare {left:?} with {right:?}"));
+    let data_type = left
+        .data_type()
+        .common_supertype(&right.data_type())
+        .ok_or_else(incomparable)?;
+    match (left.cast_to(&data_type)?, right.cast_to(&data_type)?) {
+        (ScalarValue::Boolean(left), ScalarValue::Boolean(right)) => Ok(left.partial_cmp(&right)),
+        (ScalarValue::Int32(left), ScalarValue::Int32(right)) => Ok(left.partial_cmp(&right)),
+        (ScalarValue::Int64(left), ScalarValue::Int64(right)) => Ok(left.partial_cmp(&right)),
+        (ScalarValue::Float64(left), ScalarValue::Float64(right)) => Ok(left.partial_cmp(&right)),
+        (ScalarValue::Utf8(left), ScalarValue::Utf8(right)) => Ok(left.partial_cmp(&right)),
+        (ScalarValue::Date32(left), ScalarValue::Date32(right)) => Ok(left.partial_cmp(&right)),
+        (ScalarValue::Decimal128(left, ..), ScalarValue::Decimal128(right, ..)) => {
+            Ok(left.partial_cmp(&right))
+        }
+        _ => Err(incomparable()),
+    }
+}
@@ -0,0 +1,195 @@
+use crate::error::{Error, Result};
+use crate::expr::{Alias, BinaryExpr, BinaryOperator, Case, Cast, Expr};
+use crate::schema::{DataType, Field, Schema};
+
+impl Expr {
+    /// The type of the values this expression produces against a schema.
+    pub fn get_type(&self, schema: &Schema) -> Result<DataType> {
+        match self {
+            Expr::Column(column) => Ok(schema.field_from_column(column)?.data_type),
+            Expr::Literal(value) => Ok(value.data_type()),
+            Expr::Alias(Alias { expr, .. }) => expr.get_type(schema),
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                let left_type = left.get_type(schema)?;
+                let right_type = right.get_type(schema)?;
+                match op {
+                    BinaryOperator::Plus
+                    | BinaryOperator::Minus
+                    | BinaryOperator::Multiply
+                    | BinaryOperator::Divide => {
+                        op.arithmetic_type(&left_type, &right_type).ok_or_else(|| {
+                            Error::Plan(format!(
+                                "cannot apply {op} to {left_type} and {right_type}"
+                            ))
+                        })
+                    }
+                    BinaryOperator::And | BinaryOperator::Or => Ok(DataType::Boolean),
+                    // Operands are compared as their common type
+                    _ => match left_type.common_supertype(&right_type) {
+                        Some(_) => Ok(DataType::Boolean),
+                        None => Err(Error::Plan(format!(
+                            "cannot compare {left_type} with {right_type}"
+                        ))),
+                    },
+                }
+            }
+            Expr::IsNull(_) | Expr::IsNotNull(_) => Ok(DataType::Boolean),
+            Expr::Cast(Cast { data_type, .. }) => Ok(*data_type),
+            Expr::Coalesce(args) => args.iter().try_fold(DataType::Null, |data_type, arg| {
+                let arg_type = arg.get_type(schema)?;
+                data_type.common_supertype(&arg_type).ok_or_else(|| {
+                    Error::Plan(format!(
+                        "coalesce arguments have incompatible types {data_type} and {arg_type}"
+                    ))
+                })
+            }),
+            Expr::Case(Case {
+                when_then_expr,
+                else_expr,
+                ..
+            }) => {
+                // The first result that isn't an untyped NULL decides the type
+                for result in when_then_expr
+                    .iter()
+                    .map(|(_, then)| then)
+                    .chain(else_expr.iter())
+                {
+                    let data_type = result.get_type(schema)?;
+                    if data_type != DataType::Null {
+                        return Ok(data_type);
+                    }
+                }
+                Ok(DataType::Null)
+            }
+        }
+    }
+
+    /// The field this expression contributes to a projection's output schema.
+    /// A bare column keeps its (qualified) name, an alias uses the alias name
+    /// and any other expression is named after its display form.
+    pub fn to_field(&self, input_schema: &Schema) -> Result<Field> {
+        match self {
+            Expr::Column(column) => Ok(input_schema.field_from_column(column)?.clone()),
+            Expr::Alias(Alias { name, .. }) => Ok(Field::new(
+                name.clone(),
+                self.get_type(input_schema)?,
+                self.nullable(input_schema)?,
+            )),
+            _ => Ok(Field::new(
+                self.to_string(),
+                self.get_type(input_schema)?,
+                self.nullable(input_schema)?,
+            )),
+        }
+    }
+
+    /// Whether this expression may produce NULL against a schema.
+    pub fn nullable(&self, schema: &Schema) -> Result<bool> {
+        match self {
+            Expr::Column(column) => Ok(schema.field_from_column(column)?.nullable),
+            Expr::Literal(value) => Ok(value.is_null()),
+            Expr::Alias(Alias { expr, .. }) | Expr::Cast(Cast { expr, .. }) => {
+                expr.nullable(schema)
+            }
+            Expr::BinaryExpr(BinaryExpr { left, right, .. }) => {
+                Ok(left.nullable(schema)? || right.nullable(schema)?)
+            }
+            // A null check is always either true or false
+            Expr::IsNull(_) | Expr::IsNotNull(_) => Ok(false),
+            // A single non-nullable argument guarantees a value
+            Expr::Coalesce(args) => {
+                for arg in args {
+                    if !arg.nullable(schema)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Expr::Case(Case {
+                when_then_expr,
+                else_expr,
+                ..
+            }) => {
+                // Without an ELSE, no match yields NULL
+                let Some(else_expr) = else_expr else {
+                    return Ok(true);
+                };
+                for (_, then) in when_then_expr {
+                    if then.nullable(schema)? {
+                        return Ok(true);
+                    }
+                }
+                else_expr.nullable(schema)
+            }
+        }
+    }
+}
+
+impl BinaryOperator {
+    /// The type of this arithmetic operator's result for the given operand
+    /// types, if it applies to them. Numbers are converted to their common
+    /// type; days can be added to or subtracted from a date, and subtracting
+    /// two dates gives the days between them. Decimals add, subtract and
+    /// multiply exactly, with a precision large enough for any result up to
+    /// the maximum of 38 digits.
+    pub fn arithmetic_type(&self, left: &DataType, right: &DataType) -> Option<DataType> {
+        let integer = |data_type: &DataType| {
+            matches!(
+                data_type,
+                DataType::Int32 | DataType::Int64 | DataType::Null
+            )
+        };
+        match (left, self, right) {
+            (DataType::Date32, BinaryOperator::Plus | BinaryOperator::Minus, days)
+            | (days, BinaryOperator::Plus, DataType::Date32)
+                if integer(days) =>
+            {
+                Some(DataType::Date32)
+            }
+            (DataType::Date32, BinaryOperator::Minus, DataType::Date32) => Some(DataType::Int64),
+            (decimal @ DataType::Decimal128 { .. }, _, DataType::Null)
+            | (DataType::Null, _, decimal @ DataType::Decimal128 { .. })
+                if *self != BinaryOperator::Divide =>
+            {
+                Some(*decimal)
+            }
+            (
+                DataType::Decimal128 {
+                    precision: left_precision,
+                    scale: left_scale,
+                },
+                _,
+                DataType::Decimal128 {
+                    precision: right_precision,
+                    scale: right_scale,
+                },
+            ) => {
+                let (left_precision, right_precision) =
+                    (*left_precision as i16, *right_precision as i16);
+                match self {
+                    BinaryOperator::Plus | BinaryOperator::Minus => {
+                        // One more digit for the carry
+                        let scale = *left_scale.max(right_scale);
+                        let integer_digits = (left_precision - *left_scale as i16)
+                            .max(right_precision - *right_scale as i16);
+                        Some(DataType::decimal128(
+                            integer_digits + scale as i16 + 1,
+                            scale,
+                        ))
+                    }
+                    BinaryOperator::Multiply => Some(DataType::decimal128(
+                        left_precision + right_precision + 1,
+                        left_scale.checked_add(*right_scale)?,
+                    )),
+                    _ => None,
+                }
+            }
+            _ => {
+                let numeric =
+                    |data_type: &DataType| data_type.is_numeric() || *data_type == DataType::Null;
+                left.common_supertype(right)
+                    .filter(|_| numeric(left) && numeric(right))
+            }
+        }
+    }
+}
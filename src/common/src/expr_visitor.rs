@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+use crate::column::Column;
+use crate::error::Result;
+use crate::expr::{Alias, BinaryExpr, BinaryOperator, Case, Cast, Expr};
+use crate::tree_node::TreeNodeRecursion;
+
+/// A read-only analysis over an expression tree, driven by [`Expr::accept`].
+///
+/// `f_down` is called on a node before its children, `f_up` after them.
+/// Returning `Jump` from `f_down` skips the node's children (`f_up` is still
+/// called on it); returning `Stop` from either ends the traversal.
+pub trait ExprVisitor {
+    fn f_down(&mut self, _expr: &Expr) -> Result<TreeNodeRecursion> {
+        Ok(TreeNodeRecursion::Continue)
+    }
+
+    fn f_up(&mut self, _expr: &Expr) -> Result<TreeNodeRecursion> {
+        Ok(TreeNodeRecursion::Continue)
+    }
+}
+
+impl Expr {
+    /// Walk this expression and all its sub-expressions with `visitor`,
+    /// children in evaluation order.
+    pub fn accept<V: ExprVisitor>(&self, visitor: &mut V) -> Result<TreeNodeRecursion> {
+        match visitor.f_down(self)? {
+            TreeNodeRecursion::Continue => {
+                for child in children(self) {
+                    if child.accept(visitor)? == TreeNodeRecursion::Stop {
+                        return Ok(TreeNodeRecursion::Stop);
+                    }
+                }
+            }
+            TreeNodeRecursion::Jump => {}
+            TreeNodeRecursion::Stop => return Ok(TreeNodeRecursion::Stop),
+        }
+        visitor.f_up(self)
+    }
+}
+
+impl Expr {
+    /// The columns this expression references.
+    pub fn column_refs(&self) -> HashSet<Column> {
+        struct ColumnCollector(HashSet<Column>);
+
+        impl ExprVisitor for ColumnCollector {
+            fn f_down(&mut self, expr: &Expr) -> Result<TreeNodeRecursion> {
+                if let Expr::Column(column) = expr {
+                    self.0.insert(column.clone());
+                }
+                Ok(TreeNodeRecursion::Continue)
+            }
+        }
+
+        let mut collector = ColumnCollector(HashSet::new());
+        self.accept(&mut collector)
+            .expect("collecting columns can't fail");
+        collector.0
+    }
+
+    /// Whether every column this expression references is in `columns`.
+    /// Stops at the first one that isn't.
+    pub fn references_only(&self, columns: &HashSet<Column>) -> bool {
+        struct Checker<'a> {
+            columns: &'a HashSet<Column>,
+            references_only: bool,
+        }
+
+        impl ExprVisitor for Checker<'_> {
+            fn f_down(&mut self, expr: &Expr) -> Result<TreeNodeRecursion> {
+                match expr {
+                    Expr::Column(column) if !self.columns.contains(column) => {
+                        self.references_only = false;
+                        Ok(TreeNodeRecursion::Stop)
+                    }
+                    _ => Ok(TreeNodeRecursion::Continue),
+                }
+            }
+        }
+
+        let mut checker = Checker {
+            columns,
+            references_only: true,
+        };
+        self.accept(&mut checker)
+            .expect("checking columns can't fail");
+        checker.references_only
+    }
+
+    /// Whether evaluating this expression can fail on some row, e.g. by
+    /// dividing by zero, overflowing or casting an unparseable string.
+    pub fn may_fail(&self) -> bool {
+        struct Checker(bool);
+
+        impl ExprVisitor for Checker {
+            fn f_down(&mut self, expr: &Expr) -> Result<TreeNodeRecursion> {
+                let may_fail = match expr {
+                    Expr::BinaryExpr(BinaryExpr { op, .. }) => matches!(
+                        op,
+                        BinaryOperator::Plus
+                            | BinaryOperator::Minus
+                            | BinaryOperator::Multiply
+                            | BinaryOperator::Divide
+                    ),
+                    Expr::Cast(_) => true,
+                    _ => false,
+                };
+                if !may_fail {
+                    return Ok(TreeNodeRecursion::Continue);
+                }
+                self.0 = true;
+                Ok(TreeNodeRecursion::Stop)
+            }
+        }
+
+        let mut checker = Checker(false);
+        self.accept(&mut checker)
+            .expect("checking for failures can't fail");
+        checker.0
+    }
+}
+
+impl Expr {
+    /// The number of nodes on the longest path from this expression to a
+    /// leaf, counting both; a column or literal has depth 1.
+    pub fn depth(&self) -> usize {
+        struct DepthCounter {
+            current: usize,
+            max: usize,
+        }
+
+        impl ExprVisitor for DepthCounter {
+            fn f_down(&mut self, _expr: &Expr) -> Result<TreeNodeRecursion> {
+                self.current += 1;
+                self.max = self.max.max(self.current);
+                Ok(TreeNodeRecursion::Continue)
+            }
+
+            fn f_up(&mut self, _expr: &Expr) -> Result<TreeNodeRecursion> {
+                self.current -= 1;
+                Ok(TreeNodeRecursion::Continue)
+            }
+        }
+
+        let mut counter = DepthCounter { current: 0, max: 0 };
+        self.accept(&mut counter)
+            .expect("measuring depth can't fail");
+        counter.max
+    }
+
+    /// The number of nodes in this expression, itself included.
+    pub fn size(&self) -> usize {
+        struct SizeCounter(usize);
+
+        impl ExprVisitor for SizeCounter {
+            fn f_down(&mut self, _expr: &Expr) -> Result<TreeNodeRecursion> {
+                self.0 += 1;
+                Ok(TreeNodeRecursion::Continue)
+            }
+        }
+
+        let mut counter = SizeCounter(0);
+        self.accept(&mut counter)
+            .expect("measuring size can't fail");
+        counter.0
+    }
+}
+
+/// The direct sub-expressions of `expr`, in evaluation order.
+fn children(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Column(_) | Expr::Literal(_) => vec![],
+        Expr::BinaryExpr(BinaryExpr { left, right, .. }) => vec![left, right],
+        Expr::Alias(Alias { expr, .. })
+        | Expr::Cast(Cast { expr, .. })
+        | Expr::IsNull(expr)
+        | Expr::IsNotNull(expr) => vec![expr],
+        Expr::Coalesce(args) => args.iter().collect(),
+        Expr::Case(Case {
+            expr,
+            when_then_expr,
+            else_expr,
+        }) => {
+            let mut children: Vec<&Expr> = expr.iter().map(|expr| expr.as_ref()).collect();
+            for (when, then) in when_then_expr {
+                children.push(when);
+                children.push(then);
+            }
+            children.extend(else_expr.as_deref());
+            children
+        }
+    }
+}
@@ -1,7 +1,21 @@
+pub mod analyzer;
 pub mod column;
+pub mod config;
+pub mod error;
 pub mod expr;
+pub mod expr_compile;
+pub mod expr_eval;
+pub mod expr_schema;
+pub mod expr_visitor;
 pub mod plan;
+pub mod plan_display;
+pub mod scalar;
+pub mod schema;
+pub mod spans;
+pub mod table_reference;
+pub mod testing;
 pub mod tree_node;
+pub mod validation;
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
@@ -1,9 +1,16 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
 use std::sync::Arc;
 
-use crate::expr::Expr;
+use crate::error::{Error, Result};
+use crate::expr::{Alias, Expr};
+use crate::schema::{Field, Schema};
+use crate::table_reference::TableReference;
+use crate::tree_node::{Transformed, TreeNode, TreeNodeRecursion, map_until_stop};
 
 /// A `LogicalPlan` is a node in a tree of relational operators (such as
 /// Projection or Filter).
+#[derive(Debug, Clone, PartialEq)]
 pub enum LogicalPlan {
     TableScan(TableScan),
     /// Evaluates an arbitrary list of expressions
@@ -13,15 +20,23 @@ pub enum LogicalPlan {
     Limit(Limit),
     /// Join two logical plans on one or more join columns.
     Join(Join),
+    /// Groups its input's rows and computes aggregates for each group.
+    Aggregate(Aggregate),
+    /// Orders its input's rows by one or more sort expressions.
+    Sort(Sort),
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct TableScan {
     pub table_name: String,
+    /// The schema of the whole table
+    pub table_schema: Schema,
     pub projected_columns: Vec<String>,
 }
 
 /// Projection logical plan applies a projection to its input. A projection
 /// is a list of expressions to be evaluated against the input data.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Projection {
     /// The vector of expressions
     pub expr: Vec<Expr>,
@@ -29,28 +44,589 @@ pub struct Projection {
     pub input: Arc<LogicalPlan>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Filter {
     pub predicate: Expr,
     /// The incoming logical pan
     pub input: Arc<LogicalPlan>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Limit {
     /// Maximum number of rows to fetch.
     pub fetch: usize,
     pub input: Arc<LogicalPlan>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Join {
-    left: Arc<LogicalPlan>,
-    right: Arc<LogicalPlan>,
-    on: Vec<(String, String)>,
-    join_type: JoinType,
+    pub left: Arc<LogicalPlan>,
+    pub right: Arc<LogicalPlan>,
+    pub on: Vec<(String, String)>,
+    pub join_type: JoinType,
+}
+
+/// Aggregate logical plan: one output row per distinct value of the
+/// grouping expressions, or a single row if there are none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    /// The incoming logical plan
+    pub input: Arc<LogicalPlan>,
+    /// The expressions rows are grouped by
+    pub group_expr: Vec<Expr>,
+    /// The aggregates computed for each group
+    pub aggr_expr: Vec<Expr>,
+}
+
+/// Sort logical plan: the rows of its input, ordered by the first sort
+/// expression, ties broken by the next, and so on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sort {
+    /// The sort expressions, most significant first
+    pub expr: Vec<SortExpr>,
+    /// The incoming logical plan
+    pub input: Arc<LogicalPlan>,
+}
+
+/// An expression to sort by, with its direction and where NULLs go.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortExpr {
+    pub expr: Expr,
+    pub asc: bool,
+    pub nulls_first: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JoinType {
     Inner,
     Left,
     Right,
     Full,
 }
+
+impl Projection {
+    /// Create a projection, checking that every expression resolves against
+    /// the input and that the output field names are unique.
+    pub fn try_new(expr: Vec<Expr>, input: Arc<LogicalPlan>) -> Result<Self> {
+        let input_schema = input.schema()?;
+        let mut fields = Vec::with_capacity(expr.len());
+        for expr in &expr {
+            let field = expr.to_field(&input_schema)?;
+            // An unqualified name would be ambiguous with any field of that name
+            let duplicate = fields.iter().any(|other: &Field| {
+                other.name == field.name
+                    && (other.relation == field.relation
+                        || other.relation.is_none()
+                        || field.relation.is_none())
+            });
+            if duplicate {
+                return Err(Error::SchemaError(format!(
+                    "projection outputs {expr} more than once; alias the duplicates apart"
+                )));
+            }
+            fields.push(field);
+        }
+        Ok(Self { expr, input })
+    }
+}
+
+impl SortExpr {
+    pub fn new(expr: Expr, asc: bool, nulls_first: bool) -> Self {
+        Self {
+            expr,
+            asc,
+            nulls_first,
+        }
+    }
+}
+
+impl fmt::Display for SortExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = if self.asc { "ASC" } else { "DESC" };
+        let nulls = if self.nulls_first { "FIRST" } else { "LAST" };
+        write!(f, "{} {direction} NULLS {nulls}", self.expr)
+    }
+}
+
+impl LogicalPlan {
+    /// The name of this node's operator.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogicalPlan::TableScan(_) => "TableScan",
+            LogicalPlan::Projection(_) => "Projection",
+            LogicalPlan::Filter(_) => "Filter",
+            LogicalPlan::Limit(_) => "Limit",
+            LogicalPlan::Join(_) => "Join",
+            LogicalPlan::Aggregate(_) => "Aggregate",
+            LogicalPlan::Sort(_) => "Sort",
+        }
+    }
+
+    /// The schema of the rows this plan produces.
+    pub fn schema(&self) -> Result<Schema> {
+        match self {
+            LogicalPlan::TableScan(TableScan {
+                table_name,
+                table_schema,
+                projected_columns,
+            }) => {
+                let relation = TableReference::Bare {
+                    table: table_name.clone(),
+                };
+                let fields = projected_columns
+                    .iter()
+                    .map(|name| {
+                        let field = table_schema
+                            .fields
+                            .iter()
+                            .find(|field| field.name == *name)
+                            .ok_or_else(|| {
+                                Error::SchemaError(format!(
+                                    "table {table_name} has no column {name}"
+                                ))
+                            })?;
+                        Ok(field.clone().with_relation(Some(relation.clone())))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Schema::new(fields))
+            }
+            LogicalPlan::Projection(Projection { expr, input }) => {
+                let input_schema = input.schema()?;
+                let fields = expr
+                    .iter()
+                    .map(|expr| expr.to_field(&input_schema))
+                    .collect::<Result<_>>()?;
+                Ok(Schema::new(fields))
+            }
+            LogicalPlan::Filter(Filter { input, .. })
+            | LogicalPlan::Limit(Limit { input, .. })
+            | LogicalPlan::Sort(Sort { input, .. }) => input.schema(),
+            LogicalPlan::Join(Join {
+                left,
+                right,
+                join_type,
+                ..
+            }) => left.schema()?.join(&right.schema()?, *join_type),
+            LogicalPlan::Aggregate(Aggregate {
+                input,
+                group_expr,
+                aggr_expr,
+            }) => {
+                let input_schema = input.schema()?;
+                let fields = group_expr
+                    .iter()
+                    .chain(aggr_expr)
+                    .map(|expr| expr.to_field(&input_schema))
+                    .collect::<Result<_>>()?;
+                Ok(Schema::new(fields))
+            }
+        }
+    }
+
+    /// The names of the columns this plan produces, in order.
+    pub fn output_names(&self) -> Result<Vec<String>> {
+        Ok(self
+            .schema()?
+            .fields
+            .into_iter()
+            .map(|field| field.name)
+            .collect())
+    }
+
+    /// This node's inputs, in child order.
+    pub fn inputs(&self) -> Vec<&Arc<LogicalPlan>> {
+        match self {
+            LogicalPlan::TableScan(_) => vec![],
+            LogicalPlan::Projection(Projection { input, .. })
+            | LogicalPlan::Filter(Filter { input, .. })
+            | LogicalPlan::Limit(Limit { input, .. })
+            | LogicalPlan::Aggregate(Aggregate { input, .. })
+            | LogicalPlan::Sort(Sort { input, .. }) => vec![input],
+            LogicalPlan::Join(Join { left, right, .. }) => vec![left, right],
+        }
+    }
+
+    /// The expressions this node owns, without those of its inputs.
+    pub fn expressions(&self) -> Vec<Expr> {
+        match self {
+            LogicalPlan::Projection(Projection { expr, .. }) => expr.clone(),
+            LogicalPlan::Filter(Filter { predicate, .. }) => vec![predicate.clone()],
+            LogicalPlan::Aggregate(Aggregate {
+                group_expr,
+                aggr_expr,
+                ..
+            }) => group_expr.iter().chain(aggr_expr).cloned().collect(),
+            LogicalPlan::Sort(Sort { expr, .. }) => expr
+                .iter()
+                .map(|sort_expr| sort_expr.expr.clone())
+                .collect(),
+            LogicalPlan::TableScan(_) | LogicalPlan::Limit(_) | LogicalPlan::Join(_) => vec![],
+        }
+    }
+
+    /// Rewrite the expressions this node owns with `f`, which is given the
+    /// schema they are evaluated against: that of the node's input. The
+    /// outputs of projections and aggregates keep their names: a rewritten
+    /// expression that would be named differently is aliased to its old
+    /// name. Columns and aliases keep their names however they are
+    /// rewritten.
+    pub fn rewrite_expressions<F>(&self, f: F) -> Result<Transformed<Self>>
+    where
+        F: Fn(&Expr, &Schema) -> Result<Transformed<Expr>>,
+    {
+        let (LogicalPlan::Filter(Filter { input, .. })
+        | LogicalPlan::Projection(Projection { input, .. })
+        | LogicalPlan::Aggregate(Aggregate { input, .. })
+        | LogicalPlan::Sort(Sort { input, .. })) = self
+        else {
+            return Ok(Transformed::no(self.clone()));
+        };
+        let schema = input.schema()?;
+        let keeps_names = matches!(self, LogicalPlan::Projection(_) | LogicalPlan::Aggregate(_));
+        self.clone().map_expressions(|expr| {
+            let rewritten = f(&expr, &schema)?;
+            if !keeps_names
+                || !rewritten.transformed
+                || matches!(expr, Expr::Column(_) | Expr::Alias(_))
+            {
+                return Ok(rewritten);
+            }
+            let name = expr.to_string();
+            if output_name(&rewritten.data) == name {
+                return Ok(rewritten);
+            }
+            Ok(rewritten.map_data(|expr| {
+                let expr = match expr {
+                    Expr::Alias(Alias { expr, .. }) => *expr,
+                    expr => expr,
+                };
+                Expr::Alias(Alias {
+                    expr: Box::new(expr),
+                    name,
+                })
+            }))
+        })
+    }
+
+    /// Like [`TreeNode::transform`], also returning the paths (child indices
+    /// from the root) of the nodes `f` reported as transformed, in the order
+    /// they were visited.
+    pub fn transform_tracked<F>(self, f: F) -> Result<(Transformed<Self>, Vec<Vec<usize>>)>
+    where
+        F: Fn(&Self) -> Result<Transformed<Self>>,
+    {
+        let paths = RefCell::new(vec![]);
+        let result = self.transform_with_paths(|node, path| {
+            let transformed = f(node)?;
+            if transformed.transformed {
+                paths.borrow_mut().push(path.to_vec());
+            }
+            Ok(transformed)
+        })?;
+        Ok((result, paths.into_inner()))
+    }
+
+    /// Like [`TreeNode::transform`], also passing `f` the path (child
+    /// indices from the root) of each node it is applied to.
+    pub fn transform_with_paths<F>(self, f: F) -> Result<Transformed<Self>>
+    where
+        F: Fn(&Self, &[usize]) -> Result<Transformed<Self>>,
+    {
+        transform_with_paths_impl(self, &f, vec![])
+    }
+
+    /// Call `f` on each expression this node owns, without recursing into
+    /// its inputs or into sub-expressions. Returning `Stop` skips the
+    /// remaining expressions.
+    pub fn apply_expressions<F>(&self, mut f: F) -> Result<TreeNodeRecursion>
+    where
+        F: FnMut(&Expr) -> Result<TreeNodeRecursion>,
+    {
+        let exprs = match self {
+            LogicalPlan::Projection(Projection { expr, .. }) => expr.iter().collect(),
+            LogicalPlan::Filter(Filter { predicate, .. }) => vec![predicate],
+            LogicalPlan::Aggregate(Aggregate {
+                group_expr,
+                aggr_expr,
+                ..
+            }) => group_expr.iter().chain(aggr_expr).collect(),
+            LogicalPlan::Sort(Sort { expr, .. }) => {
+                expr.iter().map(|sort_expr| &sort_expr.expr).collect()
+            }
+            LogicalPlan::TableScan(_) | LogicalPlan::Limit(_) | LogicalPlan::Join(_) => vec![],
+        };
+        for expr in exprs {
+            if f(expr)? == TreeNodeRecursion::Stop {
+                return Ok(TreeNodeRecursion::Stop);
+            }
+        }
+        Ok(TreeNodeRecursion::Continue)
+    }
+
+    /// Rewrite the expressions this node owns with `f`, without recursing
+    /// into its inputs or into sub-expressions. Returning `Stop` for an
+    /// expression leaves the remaining ones untouched.
+    pub fn map_expressions<F>(self, mut f: F) -> Result<Transformed<Self>>
+    where
+        F: FnMut(Expr) -> Result<Transformed<Expr>>,
+    {
+        match self {
+            LogicalPlan::Projection(Projection { expr, input }) => Ok(map_until_stop(expr, f)?
+                .map_data(|expr| LogicalPlan::Projection(Projection { expr, input }))),
+            LogicalPlan::Filter(Filter { predicate, input }) => Ok(f(predicate)?
+                .map_data(|predicate| LogicalPlan::Filter(Filter { predicate, input }))),
+            LogicalPlan::Aggregate(Aggregate {
+                input,
+                group_expr,
+                aggr_expr,
+            }) => {
+                let group_expr = map_until_stop(group_expr, &mut f)?;
+                // A stopped traversal leaves the aggregates untouched
+                let aggr_expr = if group_expr.tnode_recursion == TreeNodeRecursion::Stop {
+                    Transformed::new(aggr_expr, false, TreeNodeRecursion::Stop)
+                } else {
+                    map_until_stop(aggr_expr, f)?
+                };
+                Ok(Transformed::new(
+                    LogicalPlan::Aggregate(Aggregate {
+                        input,
+                        group_expr: group_expr.data,
+                        aggr_expr: aggr_expr.data,
+                    }),
+                    group_expr.transformed || aggr_expr.transformed,
+                    aggr_expr.tnode_recursion,
+                ))
+            }
+            LogicalPlan::Sort(Sort { expr, input }) => {
+                // Direction and null ordering are kept as they are
+                let (exprs, options): (Vec<_>, Vec<_>) = expr
+                    .into_iter()
+                    .map(|sort_expr| (sort_expr.expr, (sort_expr.asc, sort_expr.nulls_first)))
+                    .unzip();
+                Ok(map_until_stop(exprs, f)?.map_data(|exprs| {
+                    let expr = exprs
+                        .into_iter()
+                        .zip(options)
+                        .map(|(expr, (asc, nulls_first))| SortExpr::new(expr, asc, nulls_first))
+                        .collect();
+                    LogicalPlan::Sort(Sort { expr, input })
+                }))
+            }
+            LogicalPlan::TableScan(_) | LogicalPlan::Limit(_) | LogicalPlan::Join(_) => {
+                Ok(Transformed::no(self))
+            }
+        }
+    }
+}
+
+impl TreeNode for LogicalPlan {
+    fn apply_children<F>(&self, f: F) -> Result<Transformed<Self>>
+    where
+        F: Fn(&Self) -> Result<Transformed<Self>>,
+    {
+        match self {
+            LogicalPlan::TableScan(_) => {
+                // Leaf node - no children to transform
+                Ok(Transformed::no(self.clone()))
+            }
+            LogicalPlan::Projection(Projection { expr, input }) => {
+                Ok(f(input)?.map_data(|input| {
+                    LogicalPlan::Projection(Projection {
+                        expr: expr.clone(),
+                        input: Arc::new(input),
+                    })
+                }))
+            }
+            LogicalPlan::Filter(Filter { predicate, input }) => Ok(f(input)?.map_data(|input| {
+                LogicalPlan::Filter(Filter {
+                    predicate: predicate.clone(),
+                    input: Arc::new(input),
+                })
+            })),
+            LogicalPlan::Limit(Limit { fetch, input }) => Ok(f(input)?.map_data(|input| {
+                LogicalPlan::Limit(Limit {
+                    fetch: *fetch,
+                    input: Arc::new(input),
+                })
+            })),
+            LogicalPlan::Aggregate(Aggregate {
+                input,
+                group_expr,
+                aggr_expr,
+            }) => Ok(f(input)?.map_data(|input| {
+                LogicalPlan::Aggregate(Aggregate {
+                    input: Arc::new(input),
+                    group_expr: group_expr.clone(),
+                    aggr_expr: aggr_expr.clone(),
+                })
+            })),
+            LogicalPlan::Sort(Sort { expr, input }) => Ok(f(input)?.map_data(|input| {
+                LogicalPlan::Sort(Sort {
+                    expr: expr.clone(),
+                    input: Arc::new(input),
+                })
+            })),
+            LogicalPlan::Join(Join {
+                left,
+                right,
+                on,
+                join_type,
+            }) => {
+                let transformed_left = f(left)?;
+                // A stopped traversal leaves the right side unvisited
+                let transformed_right =
+                    if transformed_left.tnode_recursion == TreeNodeRecursion::Stop {
+                        Transformed::new(right.as_ref().clone(), false, TreeNodeRecursion::Stop)
+                    } else {
+                        f(right)?
+                    };
+                Ok(join_children(
+                    transformed_left,
+                    transformed_right,
+                    on.clone(),
+                    *join_type,
+                ))
+            }
+        }
+    }
+
+    fn map_children<F>(self, f: F) -> Result<Transformed<Self>>
+    where
+        F: Fn(Self) -> Result<Transformed<Self>>,
+    {
+        match self {
+            LogicalPlan::TableScan(_) => {
+                // Leaf node - no children to transform
+                Ok(Transformed::no(self))
+            }
+            LogicalPlan::Projection(Projection { expr, input }) => Ok(f(unwrap_arc(input))?
+                .map_data(|input| {
+                    LogicalPlan::Projection(Projection {
+                        expr,
+                        input: Arc::new(input),
+                    })
+                })),
+            LogicalPlan::Filter(Filter { predicate, input }) => {
+                Ok(f(unwrap_arc(input))?.map_data(|input| {
+                    LogicalPlan::Filter(Filter {
+                        predicate,
+                        input: Arc::new(input),
+                    })
+                }))
+            }
+            LogicalPlan::Limit(Limit { fetch, input }) => {
+                Ok(f(unwrap_arc(input))?.map_data(|input| {
+                    LogicalPlan::Limit(Limit {
+                        fetch,
+                        input: Arc::new(input),
+                    })
+                }))
+            }
+            LogicalPlan::Aggregate(Aggregate {
+                input,
+                group_expr,
+                aggr_expr,
+            }) => Ok(f(unwrap_arc(input))?.map_data(|input| {
+                LogicalPlan::Aggregate(Aggregate {
+                    input: Arc::new(input),
+                    group_expr,
+                    aggr_expr,
+                })
+            })),
+            LogicalPlan::Sort(Sort { expr, input }) => {
+                Ok(f(unwrap_arc(input))?.map_data(|input| {
+                    LogicalPlan::Sort(Sort {
+                        expr,
+                        input: Arc::new(input),
+                    })
+                }))
+            }
+            LogicalPlan::Join(Join {
+                left,
+                right,
+                on,
+                join_type,
+            }) => {
+                let transformed_left = f(unwrap_arc(left))?;
+                // A stopped traversal leaves the right side unvisited
+                let transformed_right =
+                    if transformed_left.tnode_recursion == TreeNodeRecursion::Stop {
+                        Transformed::new(unwrap_arc(right), false, TreeNodeRecursion::Stop)
+                    } else {
+                        f(unwrap_arc(right))?
+                    };
+                Ok(join_children(
+                    transformed_left,
+                    transformed_right,
+                    on,
+                    join_type,
+                ))
+            }
+        }
+    }
+}
+
+fn transform_with_paths_impl<F>(
+    plan: LogicalPlan,
+    f: &F,
+    path: Vec<usize>,
+) -> Result<Transformed<LogicalPlan>>
+where
+    F: Fn(&LogicalPlan, &[usize]) -> Result<Transformed<LogicalPlan>>,
+{
+    let index = Cell::new(0);
+    let children = plan.map_children(|child| {
+        let mut child_path = path.clone();
+        child_path.push(index.replace(index.get() + 1));
+        transform_with_paths_impl(child, f, child_path)
+    })?;
+    if children.tnode_recursion == TreeNodeRecursion::Stop {
+        return Ok(children);
+    }
+
+    let mut transformed = f(&children.data, &path)?;
+    transformed.transformed |= children.transformed;
+    if transformed.tnode_recursion == TreeNodeRecursion::Jump {
+        transformed.tnode_recursion = TreeNodeRecursion::Continue;
+    }
+    Ok(transformed)
+}
+
+/// The name of the field `expr` produces, as given by [`Expr::to_field`].
+fn output_name(expr: &Expr) -> String {
+    match expr {
+        Expr::Column(column) => column.name.clone(),
+        Expr::Alias(Alias { name, .. }) => name.clone(),
+        expr => expr.to_string(),
+    }
+}
+
+fn unwrap_arc(plan: Arc<LogicalPlan>) -> LogicalPlan {
+    Arc::try_unwrap(plan).unwrap_or_else(|arc| (*arc).clone())
+}
+
+/// Reassemble a join from its transformed inputs, combining their flags.
+fn join_children(
+    left: Transformed<LogicalPlan>,
+    right: Transformed<LogicalPlan>,
+    on: Vec<(String, String)>,
+    join_type: JoinType,
+) -> Transformed<LogicalPlan> {
+    let tnode_recursion = if left.tnode_recursion == TreeNodeRecursion::Stop
+        || right.tnode_recursion == TreeNodeRecursion::Stop
+    {
+        TreeNodeRecursion::Stop
+    } else {
+        TreeNodeRecursion::Continue
+    };
+    Transformed::new(
+        LogicalPlan::Join(Join {
+            left: Arc::new(left.data),
+            right: Arc::new(right.data),
+            on,
+            join_type,
+        }),
+        left.transformed || right.transformed,
+        tnode_recursion,
+    )
+}
@@ -0,0 +1,91 @@
+use std::fmt::Write;
+
+use crate::expr::Expr;
+use crate::plan::{Aggregate, Filter, Join, Limit, LogicalPlan, Projection, Sort, TableScan};
+
+impl LogicalPlan {
+    /// Render the plan as an indented tree, one node per line, each
+    /// annotated with its output schema like `[id:Int64, name:Utf8?]`.
+    /// Nullable fields are marked with `?`.
+    pub fn display_with_schema(&self) -> String {
+        let mut output = String::new();
+        write_node(&mut output, self, 0);
+        output
+    }
+}
+
+fn write_node(output: &mut String, plan: &LogicalPlan, indent: usize) {
+    let inputs = match plan {
+        LogicalPlan::TableScan(TableScan { table_name, .. }) => {
+            write!(output, "TableScan: {table_name}").unwrap();
+            vec![]
+        }
+        LogicalPlan::Projection(Projection { expr, input }) => {
+            let expr: Vec<_> = expr.iter().map(ToString::to_string).collect();
+            write!(output, "Projection: {}", expr.join(", ")).unwrap();
+            vec![input]
+        }
+        LogicalPlan::Filter(Filter { predicate, input }) => {
+            write!(output, "Filter: {predicate}").unwrap();
+            vec![input]
+        }
+        LogicalPlan::Limit(Limit { fetch, input }) => {
+            write!(output, "Limit: fetch={fetch}").unwrap();
+            vec![input]
+        }
+        LogicalPlan::Join(Join {
+            left,
+            right,
+            on,
+            join_type,
+        }) => {
+            let on: Vec<_> = on
+                .iter()
+                .map(|(left, right)| format!("{left} = {right}"))
+                .collect();
+            write!(output, "{join_type:?} Join: {}", on.join(", ")).unwrap();
+            vec![left, right]
+        }
+        LogicalPlan::Aggregate(Aggregate {
+            input,
+            group_expr,
+            aggr_expr,
+        }) => {
+            let list = |exprs: &[Expr]| {
+                let exprs: Vec<_> = exprs.iter().map(ToString::to_string).collect();
+                exprs.join(", ")
+            };
+            write!(
+                output,
+                "Aggregate: groupBy=[{}], aggr=[{}]",
+                list(group_expr),
+                list(aggr_expr)
+            )
+            .unwrap();
+            vec![input]
+        }
+        LogicalPlan::Sort(Sort { expr, input }) => {
+            let expr: Vec<_> = expr.iter().map(ToString::to_string).collect();
+            write!(output, "Sort: {}", expr.join(", ")).unwrap();
+            vec![input]
+        }
+    };
+    match plan.schema() {
+        Ok(schema) => {
+            let fields: Vec<_> = schema
+                .fields
+                .iter()
+                .map(|field| {
+                    let nullable = if field.nullable { "?" } else { "" };
+                    format!("{}:{}{nullable}", field.name, field.data_type)
+                })
+                .collect();
+            writeln!(output, " [{}]", fields.join(", ")).unwrap();
+        }
+        Err(error) => writeln!(output, " [{error}]").unwrap(),
+    }
+    for input in inputs {
+        output.push_str(&"  ".repeat(indent + 1));
+        write_node(output, input, indent + 1);
+    }
+}
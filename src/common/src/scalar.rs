@@ -0,0 +1,300 @@
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::schema::DataType;
+
+/// A single constant value. `None` payloads represent a typed NULL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalarValue {
+    /// An untyped NULL.
+    Null,
+    Boolean(Option<bool>),
+    Int32(Option<i32>),
+    Int64(Option<i64>),
+    Float64(Option<f64>),
+    Utf8(Option<String>),
+    /// Days since the UNIX epoch.
+    Date32(Option<i32>),
+    /// An unscaled value with its precision and scale: `Some(12345), 5, 2`
+    /// is 123.45.
+    Decimal128(Option<i128>, u8, i8),
+}
+
+impl ScalarValue {
+    /// A NULL of the given type.
+    pub fn new_null(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Null => ScalarValue::Null,
+            DataType::Boolean => ScalarValue::Boolean(None),
+            DataType::Int32 => ScalarValue::Int32(None),
+            DataType::Int64 => ScalarValue::Int64(None),
+            DataType::Float64 => ScalarValue::Float64(None),
+            DataType::Utf8 => ScalarValue::Utf8(None),
+            DataType::Date32 => ScalarValue::Date32(None),
+            DataType::Decimal128 { precision, scale } => {
+                ScalarValue::Decimal128(None, *precision, *scale)
+            }
+        }
+    }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ScalarValue::Null => DataType::Null,
+            ScalarValue::Boolean(_) => DataType::Boolean,
+            ScalarValue::Int32(_) => DataType::Int32,
+            ScalarValue::Int64(_) => DataType::Int64,
+            ScalarValue::Float64(_) => DataType::Float64,
+            ScalarValue::Utf8(_) => DataType::Utf8,
+            ScalarValue::Date32(_) => DataType::Date32,
+            ScalarValue::Decimal128(_, precision, scale) => DataType::Decimal128 {
+                precision: *precision,
+                scale: *scale,
+            },
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(
+            self,
+            ScalarValue::Null
+                | ScalarValue::Boolean(None)
+                | ScalarValue::Int32(None)
+                | ScalarValue::Int64(None)
+                | ScalarValue::Float64(None)
+                | ScalarValue::Utf8(None)
+                | ScalarValue::Date32(None)
+                | ScalarValue::Decimal128(None, ..)
+        )
+    }
+
+    /// This value with its type, e.g. `Int64(50000)` or `Utf8(NULL)`, for
+    /// when the SQL form from `Display` hides which type a NULL has.
+    pub fn debug_typed(&self) -> String {
+        match self {
+            ScalarValue::Null => "NULL".to_string(),
+            ScalarValue::Utf8(Some(value)) => format!("Utf8('{value}')"),
+            ScalarValue::Decimal128(_, precision, scale) => {
+                format!("Decimal128({self}, {precision}, {scale})")
+            }
+            _ => format!("{}({self})", self.data_type()),
+        }
+    }
+
+    /// Convert this value to another type. Fails if the value can't be
+    /// represented in the target type.
+    pub fn cast_to(&self, data_type: &DataType) -> Result<ScalarValue> {
+        if self.is_null() {
+            return Ok(ScalarValue::new_null(data_type));
+        }
+        let cast_error = || Error::Execution(format!("cannot cast {self} to {data_type}"));
+        let value = match (self, data_type) {
+            (value, data_type) if value.data_type() == *data_type => value.clone(),
+            (ScalarValue::Boolean(Some(value)), DataType::Int32) => {
+                ScalarValue::Int32(Some(*value as i32))
+            }
+            (ScalarValue::Boolean(Some(value)), DataType::Int64) => {
+                ScalarValue::Int64(Some(*value as i64))
+            }
+            (ScalarValue::Int32(Some(value)), DataType::Boolean) => {
+                ScalarValue::Boolean(Some(*value != 0))
+            }
+            (ScalarValue::Int32(Some(value)), DataType::Int64) => {
+                ScalarValue::Int64(Some(*value as i64))
+            }
+            (ScalarValue::Int32(Some(value)), DataType::Float64) => {
+                ScalarValue::Float64(Some(*value as f64))
+            }
+            (ScalarValue::Int64(Some(value)), DataType::Boolean) => {
+                ScalarValue::Boolean(Some(*value != 0))
+            }
+            (ScalarValue::Int64(Some(value)), DataType::Int32) => {
+                ScalarValue::Int32(Some(i32::try_from(*value).map_err(|_| cast_error())?))
+            }
+            (ScalarValue::Int64(Some(value)), DataType::Float64) => {
+                ScalarValue::Float64(Some(*value as f64))
+            }
+            (ScalarValue::Float64(Some(value)), DataType::Int32)
+                if value.is_finite()
+                    && value.trunc() >= i32::MIN as f64
+                    && value.trunc() <= i32::MAX as f64 =>
+            {
+                ScalarValue::Int32(Some(*value as i32))
+            }
+            (ScalarValue::Float64(Some(value)), DataType::Int64)
+                if value.is_finite()
+                    && value.trunc() >= i64::MIN as f64
+                    && value.trunc() < i64::MAX as f64 =>
+            {
+                ScalarValue::Int64(Some(*value as i64))
+            }
+            (value, DataType::Utf8) => ScalarValue::Utf8(Some(value.to_string())),
+            (ScalarValue::Utf8(Some(value)), DataType::Boolean) => {
+                ScalarValue::Boolean(Some(value.trim().parse().map_err(|_| cast_error())?))
+            }
+            (ScalarValue::Utf8(Some(value)), DataType::Int32) => {
+                ScalarValue::Int32(Some(value.trim().parse().map_err(|_| cast_error())?))
+            }
+            (ScalarValue::Utf8(Some(value)), DataType::Int64) => {
+                ScalarValue::Int64(Some(value.trim().parse().map_err(|_| cast_error())?))
+            }
+            (ScalarValue::Utf8(Some(value)), DataType::Float64) => {
+                ScalarValue::Float64(Some(value.trim().parse().map_err(|_| cast_error())?))
+            }
+            (
+                ScalarValue::Decimal128(Some(value), _, from_scale),
+                DataType::Decimal128 { precision, scale },
+            ) => {
+                let value = rescale(*value, *from_scale, *scale).ok_or_else(cast_error)?;
+                ScalarValue::Decimal128(
+                    Some(check_precision(value, *precision).ok_or_else(cast_error)?),
+                    *precision,
+                    *scale,
+                )
+            }
+            (ScalarValue::Int32(Some(value)), DataType::Decimal128 { .. }) => {
+                ScalarValue::Decimal128(Some(*value as i128), 10, 0).cast_to(data_type)?
+            }
+            (ScalarValue::Int64(Some(value)), DataType::Decimal128 { .. }) => {
+                ScalarValue::Decimal128(Some(*value as i128), 19, 0).cast_to(data_type)?
+            }
+            (ScalarValue::Decimal128(Some(value), _, scale), DataType::Float64) => {
+                ScalarValue::Float64(Some(*value as f64 / 10f64.powi(*scale as i32)))
+            }
+            (ScalarValue::Utf8(Some(value)), DataType::Decimal128 { .. }) => {
+                parse_decimal(value.trim())
+                    .ok_or_else(cast_error)?
+                    .cast_to(data_type)
+                    .map_err(|_| cast_error())?
+            }
+            (ScalarValue::Utf8(Some(value)), DataType::Date32) => {
+                ScalarValue::Date32(Some(parse_date(value.trim()).ok_or_else(cast_error)?))
+            }
+            _ => return Err(cast_error()),
+        };
+        Ok(value)
+    }
+}
+
+impl fmt::Display for ScalarValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScalarValue::Boolean(Some(value)) => write!(f, "{value}"),
+            ScalarValue::Int32(Some(value)) => write!(f, "{value}"),
+            ScalarValue::Int64(Some(value)) => write!(f, "{value}"),
+            ScalarValue::Float64(Some(value)) => write!(f, "{value}"),
+            ScalarValue::Utf8(Some(value)) => write!(f, "{value}"),
+            ScalarValue::Date32(Some(days)) => {
+                let (year, month, day) = civil_from_days(*days as i64);
+                write!(f, "{year:04}-{month:02}-{day:02}")
+            }
+            ScalarValue::Decimal128(Some(value), _, scale) => {
+                let Ok(scale) = u32::try_from(*scale) else {
+                    // A negative scale counts trailing zeros
+                    let zeros = scale.unsigned_abs() as usize;
+                    return write!(f, "{value}{}", "0".repeat(zeros));
+                };
+                let digits = value.unsigned_abs().to_string();
+                let digits = format!("{digits:0>width$}", width = scale as usize + 1);
+                let (integer, fraction) = digits.split_at(digits.len() - scale as usize);
+                let sign = if *value < 0 { "-" } else { "" };
+                if fraction.is_empty() {
+                    write!(f, "{sign}{integer}")
+                } else {
+                    write!(f, "{sign}{integer}.{fraction}")
+                }
+            }
+            _ => write!(f, "NULL"),
+        }
+    }
+}
+
+/// Change the scale of an unscaled decimal value; `None` if it overflows.
+/// Reducing the scale truncates.
+pub fn rescale(value: i128, from_scale: i8, to_scale: i8) -> Option<i128> {
+    let difference = to_scale as i32 - from_scale as i32;
+    let factor = 10i128.checked_pow(difference.unsigned_abs())?;
+    if difference >= 0 {
+        value.checked_mul(factor)
+    } else {
+        Some(value / factor)
+    }
+}
+
+/// `value` if it has at most `precision` digits.
+pub fn check_precision(value: i128, precision: u8) -> Option<i128> {
+    let limit = 10i128.checked_pow(precision as u32)?;
+    (value.unsigned_abs() < limit.unsigned_abs()).then_some(value)
+}
+
+/// Parse a decimal like `-123.45` at the scale it is written with.
+fn parse_decimal(value: &str) -> Option<ScalarValue> {
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let scale = i8::try_from(fraction.len()).ok()?;
+    let unscaled: i128 = format!("{integer}{fraction}").parse().ok()?;
+    let digits = unscaled
+        .unsigned_abs()
+        .to_string()
+        .len()
+        .max(scale as usize + 1);
+    Some(ScalarValue::Decimal128(
+        Some(unscaled),
+        u8::try_from(digits).ok()?,
+        scale,
+    ))
+}
+
+/// Parse a `YYYY-MM-DD` date into days since the UNIX epoch.
+fn parse_date(value: &str) -> Option<i32> {
+    let mut parts = value.splitn(3, '-');
+    // The day count of a year beyond i32 is too, and would overflow below
+    let year = i64::from(parts.next()?.parse::<i32>().ok()?);
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    let leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap_year => 29,
+        2 => 28,
+        _ => return None,
+    };
+    if !(1..=days_in_month).contains(&day) {
+        return None;
+    }
+    i32::try_from(days_from_civil(year, month, day)).ok()
+}
+
+// Conversions between proleptic Gregorian dates and days since 1970-01-01,
+// from Howard Hinnant's date algorithms. Eras are 400
+// year cycles starting on March 1st, so leap days fall at the end of a year.
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,287 @@
+use std::fmt;
+
+use crate::column::Column;
+use crate::config::SessionConfig;
+use crate::error::{Error, Result};
+use crate::plan::JoinType;
+use crate::table_reference::TableReference;
+
+/// The logical type of a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataType {
+    /// The type of an untyped NULL.
+    Null,
+    Boolean,
+    Int32,
+    Int64,
+    Float64,
+    Utf8,
+    /// Days since the UNIX epoch.
+    Date32,
+    /// An exact number of at most `precision` digits, `scale` of them after
+    /// the decimal point.
+    Decimal128 {
+        precision: u8,
+        scale: i8,
+    },
+}
+
+/// A named, typed column of a schema, optionally qualified by the relation it
+/// comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Field {
+    pub relation: Option<TableReference>,
+    pub name: String,
+    pub data_type: DataType,
+    pub nullable: bool,
+}
+
+/// The ordered list of fields a plan produces.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataType::Decimal128 { precision, scale } => {
+                write!(f, "Decimal128({precision}, {scale})")
+            }
+            _ => fmt::Debug::fmt(self, f),
+        }
+    }
+}
+
+/// The assumed average size in bytes of a variable-width value, for
+/// estimates.
+pub const ESTIMATED_VARIABLE_WIDTH: usize = 32;
+
+/// The largest precision of a [`DataType::Decimal128`].
+pub const DECIMAL128_MAX_PRECISION: u8 = 38;
+
+impl DataType {
+    /// A decimal type, with the precision clamped to the supported range.
+    pub fn decimal128(precision: i16, scale: i8) -> DataType {
+        DataType::Decimal128 {
+            precision: precision.clamp(1, DECIMAL128_MAX_PRECISION as i16) as u8,
+            scale,
+        }
+    }
+
+    /// Whether this is a binary number type. Decimals are exact and are not
+    /// implicitly mixed with these.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, DataType::Int32 | DataType::Int64 | DataType::Float64)
+    }
+
+    /// The size in bytes of every value of this type, or `None` for
+    /// variable-width types.
+    pub fn byte_width(&self) -> Option<usize> {
+        match self {
+            DataType::Null => Some(0),
+            DataType::Boolean => Some(1),
+            DataType::Int32 | DataType::Date32 => Some(4),
+            DataType::Int64 | DataType::Float64 => Some(8),
+            DataType::Decimal128 { .. } => Some(16),
+            DataType::Utf8 => None,
+        }
+    }
+
+    /// Whether every value of this type converts to `other` and back
+    /// unchanged. An untyped NULL converts to anything.
+    pub fn can_widen_to(&self, other: &DataType) -> bool {
+        matches!(
+            (self, other),
+            (DataType::Null, _)
+                | (DataType::Int32, DataType::Int64)
+                | (DataType::Int32, DataType::Float64)
+        ) || self == other
+    }
+
+    /// The type values of both types are converted to when they are
+    /// combined, if any.
+    pub fn common_supertype(&self, other: &DataType) -> Option<DataType> {
+        if self.can_widen_to(other) {
+            Some(*other)
+        } else if other.can_widen_to(self) {
+            Some(*self)
+        } else if self.is_numeric() && other.is_numeric() {
+            // Int64 and Float64 meet at Float64, at the cost of precision
+            Some(DataType::Float64)
+        } else if let (
+            DataType::Decimal128 {
+                precision: left_precision,
+                scale: left_scale,
+            },
+            DataType::Decimal128 {
+                precision: right_precision,
+                scale: right_scale,
+            },
+        ) = (self, other)
+        {
+            // Enough digits for the integer part and the fraction of both
+            let scale = *left_scale.max(right_scale);
+            let integer_digits = (*left_precision as i16 - *left_scale as i16)
+                .max(*right_precision as i16 - *right_scale as i16);
+            Some(DataType::decimal128(integer_digits + scale as i16, scale))
+        } else if matches!(
+            (self, other),
+            (DataType::Date32, DataType::Utf8) | (DataType::Utf8, DataType::Date32)
+        ) {
+            // So dates can be compared with string literals like '2023-01-01'
+            Some(DataType::Date32)
+        } else {
+            None
+        }
+    }
+}
+
+impl Field {
+    /// Create an unqualified field.
+    pub fn new(name: impl Into<String>, data_type: DataType, nullable: bool) -> Self {
+        Self {
+            relation: None,
+            name: name.into(),
+            data_type,
+            nullable,
+        }
+    }
+
+    pub fn with_relation(mut self, relation: Option<TableReference>) -> Self {
+        self.relation = relation;
+        self
+    }
+
+    /// The column that refers to this field.
+    pub fn column(&self) -> Column {
+        Column::new(self.relation.clone(), self.name.clone())
+    }
+}
+
+impl Schema {
+    pub fn new(fields: Vec<Field>) -> Self {
+        Self { fields }
+    }
+
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// The estimated size in bytes of a row of this schema, counting
+    /// [`ESTIMATED_VARIABLE_WIDTH`] for each variable-width field.
+    pub fn estimated_row_width(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|field| {
+                field
+                    .data_type
+                    .byte_width()
+                    .unwrap_or(ESTIMATED_VARIABLE_WIDTH)
+            })
+            .sum()
+    }
+
+    /// The schema of a join of rows of this schema with rows of `other`:
+    /// the fields of both, with those of a side that isn't preserved by an
+    /// outer join made nullable. Fields of the same name are told apart by
+    /// their relations, so they can't both be unqualified.
+    pub fn join(&self, other: &Schema, join_type: JoinType) -> Result<Schema> {
+        let (left_nullable, right_nullable) = match join_type {
+            JoinType::Inner => (false, false),
+            JoinType::Left => (false, true),
+            JoinType::Right => (true, false),
+            JoinType::Full => (true, true),
+        };
+        for field in &other.fields {
+            let collides = self.fields.iter().any(|left| {
+                left.name == field.name && left.relation.is_none() && field.relation.is_none()
+            });
+            if collides {
+                return Err(Error::SchemaError(format!(
+                    "both sides of the join have an unqualified field named {}",
+                    field.name
+                )));
+            }
+        }
+        let mut fields = Vec::with_capacity(self.fields.len() + other.fields.len());
+        for (schema, nullable) in [(self, left_nullable), (other, right_nullable)] {
+            fields.extend(schema.fields.iter().cloned().map(|mut field| {
+                field.nullable |= nullable;
+                field
+            }));
+        }
+        Ok(Schema::new(fields))
+    }
+
+    /// The fields at `indices`, in that order.
+    pub fn project(&self, indices: &[usize]) -> Result<Schema> {
+        let fields = indices
+            .iter()
+            .map(|&index| {
+                self.fields.get(index).cloned().ok_or_else(|| {
+                    Error::SchemaError(format!(
+                        "field index {index} out of range for a schema of {} fields",
+                        self.fields.len()
+                    ))
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Schema::new(fields))
+    }
+
+    /// The fields with the given unqualified names, in that order.
+    pub fn project_by_name(&self, names: &[&str]) -> Result<Schema> {
+        let indices = names
+            .iter()
+            .map(|name| self.index_of_column(&Column::new_unqualified(*name)))
+            .collect::<Result<Vec<_>>>()?;
+        self.project(&indices)
+    }
+
+    /// Find the index of the field a column refers to. An unqualified column
+    /// matches a field with the same name from any relation, as long as there
+    /// is only one. Names must match exactly.
+    pub fn index_of_column(&self, column: &Column) -> Result<usize> {
+        self.index_of_column_with(column, |left, right| left == right)
+    }
+
+    /// Find the field a column refers to, comparing identifiers as
+    /// configured for the session.
+    pub fn qualified_field(&self, column: &Column, config: &SessionConfig) -> Result<&Field> {
+        let index =
+            self.index_of_column_with(column, |left, right| config.identifiers_eq(left, right))?;
+        Ok(&self.fields[index])
+    }
+
+    fn index_of_column_with<F>(&self, column: &Column, identifiers_eq: F) -> Result<usize>
+    where
+        F: Fn(&str, &str) -> bool,
+    {
+        let relation_matches =
+            |relation: &Option<TableReference>| match (relation, &column.relation) {
+                (_, None) => true,
+                (Some(relation), Some(other)) => relation.resolved_eq_with(other, &identifiers_eq),
+                (None, Some(_)) => false,
+            };
+        let mut matches = self.fields.iter().enumerate().filter(|(_, field)| {
+            identifiers_eq(&field.name, &column.name) && relation_matches(&field.relation)
+        });
+        match (matches.next(), matches.next()) {
+            (Some((index, _)), None) => Ok(index),
+            (Some(_), Some(_)) => Err(Error::SchemaError(format!(
+                "ambiguous reference to column {}",
+                column.name
+            ))),
+            (None, _) => Err(Error::SchemaError(format!(
+                "no field named {}",
+                column.name
+            ))),
+        }
+    }
+
+    /// Find the field a column refers to.
+    pub fn field_from_column(&self, column: &Column) -> Result<&Field> {
+        Ok(&self.fields[self.index_of_column(column)?])
+    }
+}
@@ -0,0 +1,32 @@
+use std::hash::{Hash, Hasher};
+
+/// A location in the SQL source text, 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub line: u64,
+    pub column: u64,
+}
+
+/// A range of SQL source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+/// The source locations a node was built from. Spans are diagnostic only, so
+/// they never take part in equality or hashing.
+#[derive(Debug, Clone, Default)]
+pub struct Spans(pub Vec<Span>);
+
+impl PartialEq for Spans {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Spans {}
+
+impl Hash for Spans {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
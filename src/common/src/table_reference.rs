@@ -0,0 +1,186 @@
+use std::fmt;
+
+use crate::config::SessionConfig;
+use crate::error::{Error, Result};
+
+/// A reference to a table, possibly qualified.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TableReference {
+    /// An unqualified table name, e.g. `employees`.
+    Bare { table: String },
+    /// A table in a schema, e.g. `hr.employees`.
+    Partial { schema: String, table: String },
+    /// A table in a schema of a catalog, e.g. `company.hr.employees`.
+    Full {
+        catalog: String,
+        schema: String,
+        table: String,
+    },
+}
+
+impl TableReference {
+    pub fn bare(table: impl Into<String>) -> Self {
+        TableReference::Bare {
+            table: table.into(),
+        }
+    }
+
+    pub fn partial(schema: impl Into<String>, table: impl Into<String>) -> Self {
+        TableReference::Partial {
+            schema: schema.into(),
+            table: table.into(),
+        }
+    }
+
+    pub fn full(
+        catalog: impl Into<String>,
+        schema: impl Into<String>,
+        table: impl Into<String>,
+    ) -> Self {
+        TableReference::Full {
+            catalog: catalog.into(),
+            schema: schema.into(),
+            table: table.into(),
+        }
+    }
+
+    /// Parse a dotted reference such as `hr.employees`, keeping identifiers
+    /// as written. A part in double quotes may contain dots, and `""` stands
+    /// for a quote inside it.
+    pub fn parse(reference: &str) -> Result<Self> {
+        Self::from_parts(parse_parts(reference)?, reference)
+    }
+
+    /// Like [`TableReference::parse`], folding unquoted identifiers to
+    /// lowercase unless the session has case-sensitive identifiers.
+    pub fn parse_normalized(reference: &str, config: &SessionConfig) -> Result<Self> {
+        let parts = parse_parts(reference)?
+            .into_iter()
+            .map(|(part, quoted)| {
+                if quoted || config.case_sensitive_identifiers {
+                    (part, quoted)
+                } else {
+                    (part.to_lowercase(), quoted)
+                }
+            })
+            .collect();
+        Self::from_parts(parts, reference)
+    }
+
+    fn from_parts(parts: Vec<(String, bool)>, reference: &str) -> Result<Self> {
+        let mut parts = parts.into_iter().map(|(part, _)| part);
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(table), None, None, None) => Ok(Self::bare(table)),
+            (Some(schema), Some(table), None, None) => Ok(Self::partial(schema, table)),
+            (Some(catalog), Some(schema), Some(table), None) => {
+                Ok(Self::full(catalog, schema, table))
+            }
+            _ => Err(Error::Plan(format!(
+                "table reference {reference} has more than three parts"
+            ))),
+        }
+    }
+
+    pub fn table(&self) -> &str {
+        match self {
+            TableReference::Bare { table }
+            | TableReference::Partial { table, .. }
+            | TableReference::Full { table, .. } => table,
+        }
+    }
+
+    pub fn schema(&self) -> Option<&str> {
+        match self {
+            TableReference::Bare { .. } => None,
+            TableReference::Partial { schema, .. } | TableReference::Full { schema, .. } => {
+                Some(schema)
+            }
+        }
+    }
+
+    pub fn catalog(&self) -> Option<&str> {
+        match self {
+            TableReference::Full { catalog, .. } => Some(catalog),
+            _ => None,
+        }
+    }
+
+    /// Whether the two references can name the same table: the parts both
+    /// have must match, so a bare `employees` matches `hr.employees`.
+    pub fn resolved_eq(&self, other: &TableReference) -> bool {
+        self.resolved_eq_with(other, |left, right| left == right)
+    }
+
+    /// Like [`TableReference::resolved_eq`], comparing identifiers with
+    /// `identifiers_eq`.
+    pub fn resolved_eq_with<F>(&self, other: &TableReference, identifiers_eq: F) -> bool
+    where
+        F: Fn(&str, &str) -> bool,
+    {
+        let part_eq = |left: Option<&str>, right: Option<&str>| match (left, right) {
+            (Some(left), Some(right)) => identifiers_eq(left, right),
+            _ => true,
+        };
+        identifiers_eq(self.table(), other.table())
+            && part_eq(self.schema(), other.schema())
+            && part_eq(self.catalog(), other.catalog())
+    }
+}
+
+/// Split a dotted reference into its unquoted parts, each with whether it
+/// was quoted.
+fn parse_parts(reference: &str) -> Result<Vec<(String, bool)>> {
+    let invalid = || Error::Plan(format!("invalid table reference {reference}"));
+    let mut parts = vec![];
+    let mut chars = reference.chars().peekable();
+    loop {
+        let mut part = String::new();
+        let quoted = chars.peek() == Some(&'"');
+        if quoted {
+            chars.next();
+            loop {
+                match chars.next().ok_or_else(invalid)? {
+                    '"' if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        part.push('"');
+                    }
+                    '"' => break,
+                    c => part.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != '.') {
+                if c == '"' {
+                    return Err(invalid());
+                }
+                part.push(c);
+            }
+        }
+        if part.is_empty() {
+            return Err(invalid());
+        }
+        parts.push((part, quoted));
+        match chars.next() {
+            Some('.') => continue,
+            None => return Ok(parts),
+            Some(_) => return Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for TableReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = [self.catalog(), self.schema(), Some(self.table())];
+        for (index, part) in parts.into_iter().flatten().enumerate() {
+            if index > 0 {
+                write!(f, ".")?;
+            }
+            if part.contains(['.', '"']) {
+                write!(f, "\"{}\"", part.replace('"', "\"\""))?;
+            } else {
+                write!(f, "{part}")?;
+            }
+        }
+        Ok(())
+    }
+}
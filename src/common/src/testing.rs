@@ -0,0 +1,75 @@
+//! Helpers for writing tests against plans.
+
+use std::sync::Arc;
+
+use crate::column::Column;
+use crate::expr::{Alias, BinaryExpr, BinaryOperator, Expr};
+use crate::plan::{Filter, Join, JoinType, LogicalPlan};
+use crate::scalar::ScalarValue;
+use crate::table_reference::TableReference;
+
+/// An unqualified column reference.
+pub fn col(name: &str) -> Expr {
+    Expr::Column(Column::new_unqualified(name))
+}
+
+/// A column reference qualified with a bare table name.
+pub fn qualified_col(table: &str, name: &str) -> Expr {
+    Expr::Column(Column::new(Some(TableReference::bare(table)), name))
+}
+
+/// An `Int64` literal.
+pub fn lit(value: i64) -> Expr {
+    Expr::Literal(ScalarValue::Int64(Some(value)))
+}
+
+pub fn binary(left: Expr, op: BinaryOperator, right: Expr) -> Expr {
+    Expr::BinaryExpr(BinaryExpr::new(Box::new(left), op, Box::new(right)))
+}
+
+pub fn alias(expr: Expr, name: &str) -> Expr {
+    Expr::Alias(Alias {
+        expr: Box::new(expr),
+        name: name.to_string(),
+    })
+}
+
+pub fn filter(predicate: Expr, input: LogicalPlan) -> LogicalPlan {
+    LogicalPlan::Filter(Filter {
+        predicate,
+        input: Arc::new(input),
+    })
+}
+
+/// A join of `left` and `right` on their `id` columns.
+pub fn join(left: LogicalPlan, right: LogicalPlan, join_type: JoinType) -> LogicalPlan {
+    LogicalPlan::Join(Join {
+        left: Arc::new(left),
+        right: Arc::new(right),
+        on: vec![("id".to_string(), "id".to_string())],
+        join_type,
+    })
+}
+
+/// The shape of `plan` in the pattern language of [`assert_plan_shape`].
+pub fn plan_shape(plan: &LogicalPlan) -> String {
+    let inputs = plan.inputs();
+    if inputs.is_empty() {
+        return plan.name().to_string();
+    }
+    let inputs: Vec<_> = inputs.into_iter().map(|input| plan_shape(input)).collect();
+    format!("{}[{}]", plan.name(), inputs.join(", "))
+}
+
+/// Assert that `plan` has the node structure described by `pattern`, e.g.
+/// `Projection[Filter[TableScan]]` or `Join[TableScan, TableScan]`. Only
+/// node kinds are compared; whitespace in the pattern is ignored.
+#[track_caller]
+pub fn assert_plan_shape(plan: &LogicalPlan, pattern: &str) {
+    let normalize = |shape: &str| shape.split_whitespace().collect::<String>();
+    let actual = plan_shape(plan);
+    assert!(
+        normalize(&actual) == normalize(pattern),
+        "plan shape mismatch\n  expected: {pattern}\n    actual: {actual}"
+    );
+}
@@ -1,6 +1,227 @@
+use std::cell::RefCell;
+
+use crate::error::Result;
+
+/// Controls how a traversal proceeds after a node has been visited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeNodeRecursion {
+    /// Continue the traversal as usual.
+    Continue,
+    /// Skip the children of the current node, but carry on with its siblings.
+    /// Only meaningful for pre-order traversals; post-order treats it as
+    /// `Continue`.
+    Jump,
+    /// Stop the whole traversal. Nodes that have not been visited yet are
+    /// left untouched.
+    Stop,
+}
+
+/// The result of a transformation: the (possibly rewritten) node, whether it
+/// was changed, and how the traversal should continue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transformed<T> {
+    pub data: T,
+    pub transformed: bool,
+    pub tnode_recursion: TreeNodeRecursion,
+}
+
+impl<T> Transformed<T> {
+    pub fn new(data: T, transformed: bool, tnode_recursion: TreeNodeRecursion) -> Self {
+        Self {
+            data,
+            transformed,
+            tnode_recursion,
+        }
+    }
+
+    /// Node was transformed, continue the traversal.
+    pub fn yes(data: T) -> Self {
+        Self::new(data, true, TreeNodeRecursion::Continue)
+    }
+
+    /// Node was not transformed, continue the traversal.
+    pub fn no(data: T) -> Self {
+        Self::new(data, false, TreeNodeRecursion::Continue)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+
+    pub fn was_transformed(&self) -> bool {
+        self.transformed
+    }
+
+    /// Apply a function to the wrapped node, keeping the flags.
+    pub fn map_data<U, F>(self, f: F) -> Transformed<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        Transformed::new(f(self.data), self.transformed, self.tnode_recursion)
+    }
+}
+
 pub trait TreeNode: Sized {
     /// Apply a function to all children of this node.
-    fn apply_children<F>(&self, f: F) -> Result<Transformed<Self>, String>
+    fn apply_children<F>(&self, f: F) -> Result<Transformed<Self>>
+    where
+        F: Fn(&Self) -> Result<Transformed<Self>>;
+
+    /// Transform this node by applying a function to all its children
+    /// (consumes self).
+    fn map_children<F>(self, f: F) -> Result<Transformed<Self>>
+    where
+        F: Fn(Self) -> Result<Transformed<Self>>;
+
+    /// Apply a transformation function to this node and all its descendants
+    /// (post-order). A `Stop` returned for a child stops the traversal before
+    /// its remaining siblings and ancestors are visited.
+    fn transform<F>(&self, f: F) -> Result<Transformed<Self>>
     where
-        F: Fn(&Self) -> Result<Transformed<Self>, String>;
+        F: Fn(&Self) -> Result<Transformed<Self>>,
+    {
+        transform_impl(self, &f)
+    }
+
+    /// Apply a transformation function that can mutate the tree (pre-order,
+    /// consumes self). Returning `Jump` skips the node's children, returning
+    /// `Stop` ends the traversal; the change made by that call is kept either
+    /// way.
+    fn transform_down<F>(self, f: F) -> Result<Transformed<Self>>
+    where
+        F: Fn(Self) -> Result<Transformed<Self>>,
+    {
+        transform_down_impl(self, &f)
+    }
+
+    /// Rewrite this node and all its descendants with `rewriter`, calling
+    /// `f_down` on a node before its children and `f_up` after them.
+    /// Returning `Jump` from `f_down` skips the node's children (`f_up` is
+    /// still called on it); returning `Stop` from either ends the traversal.
+    fn rewrite<R: TreeNodeRewriter<Node = Self>>(
+        self,
+        rewriter: &mut R,
+    ) -> Result<Transformed<Self>> {
+        rewrite_impl(self, &RefCell::new(rewriter))
+    }
+}
+
+/// A stateful rewrite of a tree, driven by [`TreeNode::rewrite`]: the
+/// counterpart of [`TreeNode::transform`] for rewrites that need to work on
+/// both the way down and the way up, or to remember what they have seen.
+pub trait TreeNodeRewriter {
+    type Node: TreeNode;
+
+    fn f_down(&mut self, node: Self::Node) -> Result<Transformed<Self::Node>> {
+        Ok(Transformed::no(node))
+    }
+
+    fn f_up(&mut self, node: Self::Node) -> Result<Transformed<Self::Node>> {
+        Ok(Transformed::no(node))
+    }
+}
+
+fn transform_impl<N, F>(node: &N, f: &F) -> Result<Transformed<N>>
+where
+    N: TreeNode,
+    F: Fn(&N) -> Result<Transformed<N>>,
+{
+    // First, recursively transform all children
+    let children = node.apply_children(|child| transform_impl(child, f))?;
+    if children.tnode_recursion == TreeNodeRecursion::Stop {
+        return Ok(children);
+    }
+
+    // Then apply the transformation to this node
+    let mut transformed = f(&children.data)?;
+    transformed.transformed |= children.transformed;
+    if transformed.tnode_recursion == TreeNodeRecursion::Jump {
+        transformed.tnode_recursion = TreeNodeRecursion::Continue;
+    }
+    Ok(transformed)
+}
+
+fn transform_down_impl<N, F>(node: N, f: &F) -> Result<Transformed<N>>
+where
+    N: TreeNode,
+    F: Fn(N) -> Result<Transformed<N>>,
+{
+    // Apply transformation to this node first
+    let transformed = f(node)?;
+    match transformed.tnode_recursion {
+        TreeNodeRecursion::Continue => {}
+        TreeNodeRecursion::Jump => {
+            return Ok(Transformed::new(
+                transformed.data,
+                transformed.transformed,
+                TreeNodeRecursion::Continue,
+            ));
+        }
+        TreeNodeRecursion::Stop => return Ok(transformed),
+    }
+
+    // Then recursively transform children
+    let mut children = transformed
+        .data
+        .map_children(|child| transform_down_impl(child, f))?;
+    children.transformed |= transformed.transformed;
+    Ok(children)
+}
+
+fn rewrite_impl<N, R>(node: N, rewriter: &RefCell<&mut R>) -> Result<Transformed<N>>
+where
+    N: TreeNode,
+    R: TreeNodeRewriter<Node = N>,
+{
+    let down = rewriter.borrow_mut().f_down(node)?;
+    let (node, transformed) = match down.tnode_recursion {
+        TreeNodeRecursion::Continue => {
+            let children = down
+                .data
+                .map_children(|child| rewrite_impl(child, rewriter))?;
+            let transformed = down.transformed || children.transformed;
+            if children.tnode_recursion == TreeNodeRecursion::Stop {
+                return Ok(Transformed::new(
+                    children.data,
+                    transformed,
+                    TreeNodeRecursion::Stop,
+                ));
+            }
+            (children.data, transformed)
+        }
+        TreeNodeRecursion::Jump => (down.data, down.transformed),
+        TreeNodeRecursion::Stop => return Ok(down),
+    };
+
+    let mut up = rewriter.borrow_mut().f_up(node)?;
+    up.transformed |= transformed;
+    if up.tnode_recursion == TreeNodeRecursion::Jump {
+        up.tnode_recursion = TreeNodeRecursion::Continue;
+    }
+    Ok(up)
+}
+
+/// Apply a function to each node in order until one of them returns `Stop`;
+/// the remaining nodes are left untouched.
+pub fn map_until_stop<T, F>(nodes: Vec<T>, mut f: F) -> Result<Transformed<Vec<T>>>
+where
+    F: FnMut(T) -> Result<Transformed<T>>,
+{
+    let mut transformed = false;
+    let mut tnode_recursion = TreeNodeRecursion::Continue;
+    let data = nodes
+        .into_iter()
+        .map(|node| {
+            if tnode_recursion == TreeNodeRecursion::Stop {
+                return Ok(node);
+            }
+            let result = f(node)?;
+            transformed |= result.transformed;
+            if result.tnode_recursion == TreeNodeRecursion::Stop {
+                tnode_recursion = TreeNodeRecursion::Stop;
+            }
+            Ok(result.data)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Transformed::new(data, transformed, tnode_recursion))
 }
@@ -0,0 +1,117 @@
+use crate::column::Column;
+use crate::error::{Error, Result};
+use crate::plan::{Filter, Join, LogicalPlan, Projection, Sort, TableScan};
+use crate::schema::{DataType, Schema};
+
+impl LogicalPlan {
+    /// Check that every node of the plan is well-formed: its expressions
+    /// resolve against its input and have types the node can use. Errors
+    /// report the path to the offending node.
+    pub fn validate(&self) -> Result<()> {
+        validate_node(self, &mut vec![])
+    }
+
+    /// Check the internal consistency of the plan: everything
+    /// [`LogicalPlan::validate`] checks, and that no node produces the same
+    /// column twice. A plan built by the planner and rewritten by correct
+    /// rules always passes, so errors are `Internal`, with the path to the
+    /// offending node.
+    pub fn assert_invariants(&self) -> Result<()> {
+        assert_invariants_at(self, &mut vec![])
+    }
+}
+
+fn assert_invariants_at(plan: &LogicalPlan, path: &mut Vec<usize>) -> Result<()> {
+    for (index, input) in plan.inputs().into_iter().enumerate() {
+        path.push(index);
+        assert_invariants_at(input, path)?;
+        path.pop();
+    }
+
+    check_node(plan)
+        .and_then(|()| check_unique_columns(plan))
+        .map_err(|error| {
+            Error::Internal(format!("invariant violated: {error}"))
+                .at_node(plan.name(), path.clone())
+        })
+}
+
+fn check_unique_columns(plan: &LogicalPlan) -> Result<()> {
+    match plan {
+        LogicalPlan::TableScan(TableScan {
+            projected_columns, ..
+        }) => {
+            for (index, column) in projected_columns.iter().enumerate() {
+                if projected_columns[..index].contains(column) {
+                    return Err(Error::Plan(format!(
+                        "scan projects {column} more than once"
+                    )));
+                }
+            }
+            Ok(())
+        }
+        LogicalPlan::Projection(Projection { expr, input }) => {
+            Projection::try_new(expr.clone(), input.clone()).map(|_| ())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_node(plan: &LogicalPlan, path: &mut Vec<usize>) -> Result<()> {
+    // Validate the inputs first so the deepest invalid node is reported
+    for (index, input) in plan.inputs().into_iter().enumerate() {
+        path.push(index);
+        validate_node(input, path)?;
+        path.pop();
+    }
+
+    check_node(plan).map_err(|error| error.at_node(plan.name(), path.clone()))
+}
+
+fn check_node(plan: &LogicalPlan) -> Result<()> {
+    match plan {
+        LogicalPlan::Filter(Filter { predicate, input }) => {
+            let data_type = predicate.get_type(&input.schema()?)?;
+            if !matches!(data_type, DataType::Boolean | DataType::Null) {
+                return Err(Error::Plan(format!(
+                    "filter predicate {predicate} must be Boolean, not {data_type}"
+                )));
+            }
+        }
+        LogicalPlan::Join(Join {
+            left, right, on, ..
+        }) => {
+            let (left_schema, right_schema) = (left.schema()?, right.schema()?);
+            let resolves = |schema: &Schema, name: &str| {
+                schema
+                    .index_of_column(&Column::new_unqualified(name))
+                    .is_ok()
+            };
+            for (left_name, right_name) in on {
+                if resolves(&left_schema, left_name) && resolves(&right_schema, right_name) {
+                    continue;
+                }
+                let hint =
+                    if resolves(&left_schema, right_name) && resolves(&right_schema, left_name) {
+                        " (the columns appear to be swapped)"
+                    } else {
+                        ""
+                    };
+                return Err(Error::Plan(format!(
+                    "join key ({left_name}, {right_name}) must name a left input column and \
+                     a right input column{hint}"
+                )));
+            }
+        }
+        LogicalPlan::Sort(Sort { expr, input }) => {
+            let schema = input.schema()?;
+            for sort_expr in expr {
+                sort_expr.expr.get_type(&schema)?;
+            }
+        }
+        _ => {
+            plan.schema()?;
+        }
+    }
+    Ok(())
+}
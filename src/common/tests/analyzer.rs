@@ -0,0 +1,180 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use common::analyzer::{Analyzer, AnalyzerRule, TypeCoercion};
+use common::error::{Error, Result};
+use common::expr::{BinaryExpr, BinaryOperator, Expr};
+use common::plan::{Aggregate, Filter, LogicalPlan, Projection, Sort, SortExpr, TableScan};
+use common::scalar::ScalarValue;
+use common::schema::{DataType, Field, Schema};
+use common::testing::{binary, col};
+
+fn scan() -> LogicalPlan {
+    LogicalPlan::TableScan(TableScan {
+        table_name: "employees".to_string(),
+        table_schema: Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+        ]),
+        projected_columns: vec!["id".to_string(), "name".to_string()],
+    })
+}
+
+fn filter(predicate: Expr) -> LogicalPlan {
+    LogicalPlan::Filter(Filter {
+        predicate,
+        input: Arc::new(scan()),
+    })
+}
+
+#[test]
+fn test_analyzer_qualifies_and_coerces() {
+    let plan = filter(binary(
+        col("ID"),
+        BinaryOperator::Gt,
+        Expr::Literal(ScalarValue::Int64(Some(10))),
+    ));
+    let plan = Analyzer::new().analyze(&plan).unwrap();
+    let LogicalPlan::Filter(Filter { predicate, .. }) = plan else {
+        panic!("expected a filter");
+    };
+    assert_eq!(predicate.to_string(), "CAST(employees.id AS Int64) > 10");
+}
+
+#[test]
+fn test_analyzer_keeps_projection_names() {
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![binary(
+            col("id"),
+            BinaryOperator::Plus,
+            Expr::Literal(ScalarValue::Int64(Some(1))),
+        )],
+        input: Arc::new(scan()),
+    });
+    let analyzed = Analyzer::new().analyze(&plan).unwrap();
+    assert_eq!(
+        analyzed.output_names().unwrap(),
+        plan.output_names().unwrap()
+    );
+}
+
+#[test]
+fn test_analyzer_rejects_incomparable_types() {
+    let plan = filter(binary(
+        col("name"),
+        BinaryOperator::Eq,
+        Expr::Literal(ScalarValue::Boolean(Some(true))),
+    ));
+    let error = Analyzer::new().analyze(&plan).unwrap_err();
+    assert!(matches!(error, Error::Plan(_)), "{error}");
+}
+
+/// Records its name when run, failing if `fail` is set.
+struct Recorder {
+    name: &'static str,
+    fail: bool,
+    log: Rc<RefCell<Vec<&'static str>>>,
+}
+
+impl AnalyzerRule for Recorder {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn analyze(&self, plan: &LogicalPlan) -> Result<LogicalPlan> {
+        self.log.borrow_mut().push(self.name);
+        if self.fail {
+            return Err(Error::Plan(format!("{} failed", self.name)));
+        }
+        Ok(plan.clone())
+    }
+}
+
+#[test]
+fn test_default_rule_order() {
+    let analyzer = Analyzer::default();
+    let names: Vec<_> = analyzer.rules.iter().map(|rule| rule.name()).collect();
+    assert_eq!(names, ["qualify_columns", "type_coercion"]);
+}
+
+#[test]
+fn test_rules_run_in_order_until_one_fails() {
+    let log = Rc::new(RefCell::new(vec![]));
+    let rule = |name, fail| -> Box<dyn AnalyzerRule> {
+        Box::new(Recorder {
+            name,
+            fail,
+            log: log.clone(),
+        })
+    };
+    let analyzer = Analyzer::with_rules(vec![rule("first", false), rule("second", false)]);
+    analyzer.analyze(&scan()).unwrap();
+    assert_eq!(*log.borrow(), ["first", "second"]);
+
+    log.borrow_mut().clear();
+    let analyzer =
+        Analyzer::with_rules(vec![Box::new(TypeCoercion), rule("after_coercion", false)]);
+    let plan = filter(binary(
+        col("name"),
+        BinaryOperator::Lt,
+        Expr::Literal(ScalarValue::Int64(Some(1))),
+    ));
+    let error = analyzer.analyze(&plan).unwrap_err();
+    assert!(matches!(error, Error::Plan(_)), "{error}");
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn test_untyped_null_takes_operand_type() {
+    let null = Expr::Literal(ScalarValue::Null);
+    let plan = filter(binary(col("id"), BinaryOperator::Eq, null.clone()));
+    let LogicalPlan::Filter(Filter { predicate, .. }) = Analyzer::new().analyze(&plan).unwrap()
+    else {
+        panic!("expected a filter");
+    };
+    let Expr::BinaryExpr(BinaryExpr { right, .. }) = predicate else {
+        panic!("expected a comparison");
+    };
+    assert_eq!(*right, Expr::Literal(ScalarValue::Int32(None)));
+
+    // NULL days added to a date are not a date
+    let date = Expr::Literal(ScalarValue::Date32(Some(0)));
+    let sum = binary(date, BinaryOperator::Plus, null);
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![sum.clone()],
+        input: Arc::new(scan()),
+    });
+    let LogicalPlan::Projection(Projection { expr, .. }) = Analyzer::new().analyze(&plan).unwrap()
+    else {
+        panic!("expected a projection");
+    };
+    assert_eq!(expr, vec![sum]);
+}
+
+#[test]
+fn test_analyzer_rewrites_aggregate_and_sort() {
+    let id_plus_one = binary(
+        col("ID"),
+        BinaryOperator::Plus,
+        Expr::Literal(ScalarValue::Int64(Some(1))),
+    );
+    let aggregate = LogicalPlan::Aggregate(Aggregate {
+        input: Arc::new(scan()),
+        group_expr: vec![col("NAME")],
+        aggr_expr: vec![id_plus_one],
+    });
+    let plan = LogicalPlan::Sort(Sort {
+        expr: vec![SortExpr::new(col("Name"), true, false)],
+        input: Arc::new(aggregate),
+    });
+
+    let analyzed = Analyzer::new().analyze(&plan).unwrap();
+    let expected = "\
+Sort: employees.name ASC NULLS LAST [name:Utf8?, ID + 1:Int64]
+  Aggregate: groupBy=[employees.name], aggr=[CAST(employees.id AS Int64) + 1 AS ID + 1] \
+[name:Utf8?, ID + 1:Int64]
+    TableScan: employees [id:Int32, name:Utf8?]
+";
+    assert_eq!(analyzed.display_with_schema(), expected);
+}
@@ -0,0 +1,18 @@
+use common::column::Column;
+use common::spans::Spans;
+use common::table_reference::TableReference;
+
+#[test]
+fn test_column_constructors() {
+    let column = Column::new(Some(TableReference::bare("employees")), "id");
+    assert_eq!(column.relation, Some(TableReference::bare("employees")));
+    assert_eq!(column.spans, Spans::default());
+    assert_eq!(column.to_string(), "employees.id");
+
+    let column = Column::new(Some(TableReference::partial("hr", "employees")), "id");
+    assert_eq!(column.to_string(), "hr.employees.id");
+
+    let column = Column::new_unqualified("salary");
+    assert_eq!(column.relation, None);
+    assert_eq!(column.to_string(), "salary");
+}
@@ -0,0 +1,57 @@
+use std::error::Error as _;
+use std::io;
+
+use common::error::{Error, Result};
+
+fn read_file(path: &str) -> Result<String> {
+    Ok(std::fs::read_to_string(path)?)
+}
+
+fn parse_fetch(value: &str) -> Result<usize> {
+    Ok(value.parse()?)
+}
+
+#[test]
+fn test_io_error_source() {
+    let error = read_file("does/not/exist.csv").unwrap_err();
+    assert!(matches!(error, Error::IoError(_)));
+
+    let source = error.source().expect("io error has a source");
+    let io_error = source
+        .downcast_ref::<io::Error>()
+        .expect("source is an io error");
+    assert_eq!(io_error.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_parse_int_error_source() {
+    let error = parse_fetch("ten").unwrap_err();
+    assert_eq!(error.to_string(), "Parse error");
+    assert!(
+        error
+            .source()
+            .is_some_and(|source| source.is::<std::num::ParseIntError>())
+    );
+}
+
+#[test]
+fn test_error_chain() {
+    let error = read_file("does/not/exist.csv")
+        .unwrap_err()
+        .at_node("TableScan", vec![0]);
+
+    // AtNode(IoError) -> io::Error
+    let mut chain = vec![error.to_string()];
+    let mut source = error.source();
+    while let Some(error) = source {
+        chain.push(error.to_string());
+        source = error.source();
+    }
+    assert_eq!(chain.len(), 2);
+    // Each message appears once; the node is shown with the error it's for
+    assert_eq!(chain[0], "IO error at TableScan (root/0)");
+    assert!(!chain[1].starts_with("IO error"), "{}", chain[1]);
+
+    // Plain messages have no cause
+    assert!(Error::Plan("invalid".to_string()).source().is_none());
+}
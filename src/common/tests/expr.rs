@@ -0,0 +1,60 @@
+use common::expr::{BinaryExpr, BinaryOperator, Expr};
+use common::scalar::ScalarValue;
+use common::testing::{col, lit};
+
+#[test]
+fn test_binary_expr_new() {
+    let expr = BinaryExpr::new(
+        Box::new(col("salary")),
+        BinaryOperator::Lt,
+        Box::new(lit(10)),
+    );
+    assert_eq!(expr.left(), &col("salary"));
+    assert_eq!(expr.op(), BinaryOperator::Lt);
+    assert_eq!(expr.right(), &lit(10));
+    assert_eq!(Expr::BinaryExpr(expr).to_string(), "salary < 10");
+}
+
+#[test]
+fn test_swap_operands() {
+    let expr = BinaryExpr::new(
+        Box::new(col("salary")),
+        BinaryOperator::Lt,
+        Box::new(lit(10)),
+    );
+    let swapped = expr.swap_operands().unwrap();
+    assert_eq!(Expr::BinaryExpr(swapped).to_string(), "10 > salary");
+
+    let expr = BinaryExpr::new(Box::new(col("a")), BinaryOperator::Eq, Box::new(col("b")));
+    let swapped = expr.swap_operands().unwrap();
+    assert_eq!(Expr::BinaryExpr(swapped).to_string(), "b = a");
+
+    // a - b can't be written with b on the left
+    let expr = BinaryExpr::new(
+        Box::new(col("a")),
+        BinaryOperator::Minus,
+        Box::new(col("b")),
+    );
+    assert_eq!(expr.swap_operands(), None);
+}
+
+#[test]
+fn test_variant_accessors() {
+    let column = col("salary");
+    let literal = lit(10);
+    let binary = Expr::BinaryExpr(BinaryExpr::new(
+        Box::new(column.clone()),
+        BinaryOperator::Lt,
+        Box::new(literal.clone()),
+    ));
+
+    assert_eq!(column.as_column().unwrap().name, "salary");
+    assert_eq!(literal.as_column(), None);
+    assert_eq!(literal.as_literal(), Some(&ScalarValue::Int64(Some(10))));
+    assert_eq!(binary.as_literal(), None);
+    assert_eq!(binary.as_binary().unwrap().op(), BinaryOperator::Lt);
+    assert_eq!(column.as_binary(), None);
+
+    assert_eq!(column.clone().try_into_column().unwrap().name, "salary");
+    assert_eq!(binary.clone().try_into_column(), Err(binary));
+}
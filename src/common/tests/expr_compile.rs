@@ -0,0 +1,73 @@
+use common::error::Error;
+use common::expr::{BinaryOperator, Case, Cast, Expr};
+use common::expr_compile::compile_expr;
+use common::scalar::ScalarValue;
+use common::schema::{DataType, Field, Schema};
+use common::testing::{binary, col, lit};
+
+fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("salary", DataType::Int64, true),
+    ])
+}
+
+#[test]
+fn test_compiled_matches_interpreter() {
+    let schema = schema();
+    let exprs = [
+        binary(col("salary"), BinaryOperator::Multiply, lit(2)),
+        binary(
+            binary(col("id"), BinaryOperator::Lt, lit(3)),
+            BinaryOperator::Or,
+            Expr::IsNull(Box::new(col("salary"))),
+        ),
+        Expr::Case(Case {
+            expr: Some(Box::new(col("id"))),
+            when_then_expr: vec![(Box::new(lit(1)), Box::new(col("name")))],
+            else_expr: Some(Box::new(Expr::Literal(ScalarValue::Utf8(Some(
+                "other".to_string(),
+            ))))),
+        }),
+        Expr::Coalesce(vec![col("salary"), col("id")]),
+        // Short-circuits before dividing by zero
+        binary(
+            binary(col("id"), BinaryOperator::Gt, lit(10)),
+            BinaryOperator::And,
+            binary(
+                binary(col("id"), BinaryOperator::Divide, lit(0)),
+                BinaryOperator::Gt,
+                lit(1),
+            ),
+        ),
+        Expr::Cast(Cast {
+            expr: Box::new(col("id")),
+            data_type: DataType::Utf8,
+        }),
+    ];
+    let rows = (0..4).map(|id| {
+        vec![
+            ScalarValue::Int64(Some(id)),
+            ScalarValue::Utf8(Some(format!("employee {id}"))),
+            ScalarValue::Int64((id % 2 == 0).then_some(id * 1000)),
+        ]
+    });
+    let rows: Vec<_> = rows.collect();
+    for expr in &exprs {
+        let compiled = compile_expr(expr, &schema).unwrap();
+        for row in &rows {
+            assert_eq!(
+                compiled.evaluate(row).unwrap(),
+                expr.evaluate(&schema, row).unwrap(),
+                "{expr}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_compile_resolves_columns_up_front() {
+    let result = compile_expr(&col("bonus"), &schema());
+    assert!(matches!(result, Err(Error::SchemaError(_))));
+}
@@ -0,0 +1,392 @@
+use common::error::Error;
+use common::expr::{BinaryOperator, Case, Cast, Expr};
+use common::scalar::ScalarValue;
+use common::schema::{DataType, Field, Schema};
+use common::testing::{binary, col, lit};
+
+fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("salary", DataType::Int64, true),
+    ])
+}
+
+fn row(salary: Option<i64>) -> Vec<ScalarValue> {
+    vec![
+        ScalarValue::Int64(Some(1)),
+        ScalarValue::Utf8(Some("Bill".to_string())),
+        ScalarValue::Int64(salary),
+    ]
+}
+
+fn lit_bool(value: Option<bool>) -> Expr {
+    Expr::Literal(ScalarValue::Boolean(value))
+}
+
+fn date(value: &str) -> ScalarValue {
+    ScalarValue::Utf8(Some(value.to_string()))
+        .cast_to(&DataType::Date32)
+        .unwrap()
+}
+
+fn decimal(value: &str, precision: u8, scale: i8) -> ScalarValue {
+    ScalarValue::Utf8(Some(value.to_string()))
+        .cast_to(&DataType::Decimal128 { precision, scale })
+        .unwrap()
+}
+
+#[test]
+fn test_evaluate_column_and_arithmetic() {
+    let expr = binary(col("salary"), BinaryOperator::Plus, lit(500));
+    assert_eq!(
+        expr.evaluate(&schema(), &row(Some(12000))).unwrap(),
+        ScalarValue::Int64(Some(12500))
+    );
+    assert_eq!(
+        expr.evaluate(&schema(), &row(None)).unwrap(),
+        ScalarValue::Int64(None)
+    );
+
+    let overflow = binary(lit(i64::MAX), BinaryOperator::Plus, lit(1));
+    assert!(matches!(
+        overflow.evaluate(&schema(), &row(None)),
+        Err(Error::Execution(_))
+    ));
+}
+
+#[test]
+fn test_evaluate_comparison_and_logic() {
+    let expr = binary(col("salary"), BinaryOperator::Gt, lit(11000));
+    assert_eq!(
+        expr.evaluate(&schema(), &row(Some(12000))).unwrap(),
+        ScalarValue::Boolean(Some(true))
+    );
+    assert_eq!(
+        expr.evaluate(&schema(), &row(None)).unwrap(),
+        ScalarValue::Boolean(None)
+    );
+
+    // NULL AND false is false, NULL OR true is true, otherwise NULL wins
+    let null = lit_bool(None);
+    let eval = |expr: Expr| expr.evaluate(&schema(), &row(None)).unwrap();
+    assert_eq!(
+        eval(binary(
+            null.clone(),
+            BinaryOperator::And,
+            lit_bool(Some(false))
+        )),
+        ScalarValue::Boolean(Some(false))
+    );
+    assert_eq!(
+        eval(binary(
+            null.clone(),
+            BinaryOperator::Or,
+            lit_bool(Some(true))
+        )),
+        ScalarValue::Boolean(Some(true))
+    );
+    assert_eq!(
+        eval(binary(null, BinaryOperator::And, lit_bool(Some(true)))),
+        ScalarValue::Boolean(None)
+    );
+}
+
+#[test]
+fn test_evaluate_logic_short_circuits() {
+    // Evaluating the right operand would fail
+    let division_by_zero = binary(
+        binary(col("id"), BinaryOperator::Divide, lit(0)),
+        BinaryOperator::Gt,
+        lit(1),
+    );
+    let eval = |expr: Expr| expr.evaluate(&schema(), &row(None));
+    assert_eq!(
+        eval(binary(
+            lit_bool(Some(false)),
+            BinaryOperator::And,
+            division_by_zero.clone()
+        ))
+        .unwrap(),
+        ScalarValue::Boolean(Some(false))
+    );
+    assert_eq!(
+        eval(binary(
+            lit_bool(Some(true)),
+            BinaryOperator::Or,
+            division_by_zero.clone()
+        ))
+        .unwrap(),
+        ScalarValue::Boolean(Some(true))
+    );
+
+    // Neither NULL nor the other value decides the result on its own
+    for left in [lit_bool(None), lit_bool(Some(true))] {
+        let result = eval(binary(left, BinaryOperator::And, division_by_zero.clone()));
+        assert!(matches!(result, Err(Error::Execution(_))));
+    }
+    let result = eval(binary(lit_bool(None), BinaryOperator::Or, division_by_zero));
+    assert!(matches!(result, Err(Error::Execution(_))));
+}
+
+#[test]
+fn test_evaluate_case_and_null_checks() {
+    let expr = Expr::Case(Case {
+        expr: None,
+        when_then_expr: vec![(
+            Box::new(Expr::IsNull(Box::new(col("salary")))),
+            Box::new(lit(0)),
+        )],
+        else_expr: Some(Box::new(col("salary"))),
+    });
+    assert_eq!(
+        expr.evaluate(&schema(), &row(None)).unwrap(),
+        ScalarValue::Int64(Some(0))
+    );
+    assert_eq!(
+        expr.evaluate(&schema(), &row(Some(10000))).unwrap(),
+        ScalarValue::Int64(Some(10000))
+    );
+}
+
+#[test]
+fn test_evaluate_coalesce() {
+    let expr = Expr::Coalesce(vec![col("salary"), lit(0)]);
+    assert_eq!(
+        expr.evaluate(&schema(), &row(Some(10000))).unwrap(),
+        ScalarValue::Int64(Some(10000))
+    );
+    assert_eq!(
+        expr.evaluate(&schema(), &row(None)).unwrap(),
+        ScalarValue::Int64(Some(0))
+    );
+
+    // All NULL yields NULL
+    let expr = Expr::Coalesce(vec![col("salary")]);
+    assert_eq!(
+        expr.evaluate(&schema(), &row(None)).unwrap(),
+        ScalarValue::Int64(None)
+    );
+}
+
+#[test]
+fn test_evaluate_multiply_and_divide() {
+    let expr = binary(col("salary"), BinaryOperator::Multiply, lit(2));
+    assert_eq!(
+        expr.evaluate(&schema(), &row(Some(12000))).unwrap(),
+        ScalarValue::Int64(Some(24000))
+    );
+
+    let expr = binary(col("salary"), BinaryOperator::Divide, lit(0));
+    assert!(matches!(
+        expr.evaluate(&schema(), &row(Some(12000))),
+        Err(Error::Execution(_))
+    ));
+}
+
+#[test]
+fn test_evaluate_cast() {
+    let cast = |expr: Expr, data_type: DataType| {
+        Expr::Cast(Cast {
+            expr: Box::new(expr),
+            data_type,
+        })
+    };
+
+    let narrowed = cast(col("salary"), DataType::Int32);
+    assert_eq!(
+        narrowed.evaluate(&schema(), &row(Some(12000))).unwrap(),
+        ScalarValue::Int32(Some(12000))
+    );
+    assert_eq!(
+        narrowed.evaluate(&schema(), &row(None)).unwrap(),
+        ScalarValue::Int32(None)
+    );
+    assert!(narrowed.evaluate(&schema(), &row(Some(i64::MAX))).is_err());
+
+    // Mixed arithmetic is computed in the common type
+    let halved = binary(
+        cast(col("salary"), DataType::Float64),
+        BinaryOperator::Divide,
+        lit(8),
+    );
+    assert_eq!(
+        halved.evaluate(&schema(), &row(Some(12))).unwrap(),
+        ScalarValue::Float64(Some(1.5))
+    );
+
+    let parsed = cast(
+        Expr::Literal(ScalarValue::Utf8(Some("42".to_string()))),
+        DataType::Int64,
+    );
+    assert_eq!(
+        binary(parsed, BinaryOperator::Eq, lit(42))
+            .evaluate(&schema(), &row(None))
+            .unwrap(),
+        ScalarValue::Boolean(Some(true))
+    );
+}
+
+#[test]
+fn test_date_literal_cast() {
+    assert_eq!(date("1970-01-01"), ScalarValue::Date32(Some(0)));
+    assert_eq!(date("2000-03-01"), ScalarValue::Date32(Some(11017)));
+    assert_eq!(date("1969-12-31"), ScalarValue::Date32(Some(-1)));
+    assert_eq!(date("2024-02-29").to_string(), "2024-02-29");
+    assert_eq!(
+        date("2023-01-01").cast_to(&DataType::Utf8).unwrap(),
+        ScalarValue::Utf8(Some("2023-01-01".to_string()))
+    );
+
+    // Out of range years are rejected, not overflowed
+    let out_of_range = ["9000000000000000000-01-01", "2147483647-01-01"];
+    for invalid in ["2023-02-29", "2023-13-01", "2023-01", "yesterday"]
+        .into_iter()
+        .chain(out_of_range)
+    {
+        let value = ScalarValue::Utf8(Some(invalid.to_string()));
+        assert!(value.cast_to(&DataType::Date32).is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn test_evaluate_date_arithmetic() {
+    let evaluate = |left: ScalarValue, op: BinaryOperator, right: ScalarValue| {
+        binary(Expr::Literal(left), op, Expr::Literal(right))
+            .evaluate(&schema(), &row(None))
+            .unwrap()
+    };
+
+    assert_eq!(
+        evaluate(
+            date("2023-12-31"),
+            BinaryOperator::Plus,
+            ScalarValue::Int64(Some(1))
+        ),
+        date("2024-01-01")
+    );
+    assert_eq!(
+        evaluate(
+            ScalarValue::Int32(Some(30)),
+            BinaryOperator::Plus,
+            date("2024-02-01")
+        ),
+        date("2024-03-02")
+    );
+    assert_eq!(
+        evaluate(
+            date("2024-03-01"),
+            BinaryOperator::Minus,
+            ScalarValue::Int64(Some(1))
+        ),
+        date("2024-02-29")
+    );
+    assert_eq!(
+        evaluate(
+            date("2024-01-01"),
+            BinaryOperator::Minus,
+            date("2023-01-01")
+        ),
+        ScalarValue::Int64(Some(365))
+    );
+    assert_eq!(
+        evaluate(
+            date("2024-01-01"),
+            BinaryOperator::Plus,
+            ScalarValue::Int64(None)
+        ),
+        ScalarValue::Date32(None)
+    );
+
+    // Dates compare with date strings
+    assert_eq!(
+        evaluate(date("2023-06-01"), BinaryOperator::Gt, date("2023-01-01")),
+        ScalarValue::Boolean(Some(true))
+    );
+    assert_eq!(
+        evaluate(
+            date("2023-06-01"),
+            BinaryOperator::Gt,
+            ScalarValue::Utf8(Some("2023-01-01".to_string()))
+        ),
+        ScalarValue::Boolean(Some(true))
+    );
+}
+
+#[test]
+fn test_decimal_cast_and_display() {
+    assert_eq!(
+        decimal("123.45", 5, 2),
+        ScalarValue::Decimal128(Some(12345), 5, 2)
+    );
+    assert_eq!(decimal("-0.5", 4, 2).to_string(), "-0.50");
+    assert_eq!(decimal("7", 3, 0).to_string(), "7");
+    assert_eq!(ScalarValue::Decimal128(Some(5), 3, 3).to_string(), "0.005");
+    assert_eq!(
+        ScalarValue::Int64(Some(42))
+            .cast_to(&DataType::Decimal128 {
+                precision: 4,
+                scale: 1
+            })
+            .unwrap(),
+        ScalarValue::Decimal128(Some(420), 4, 1)
+    );
+
+    // Too many digits for the precision
+    let value = ScalarValue::Utf8(Some("1234.5".to_string()));
+    let result = value.cast_to(&DataType::Decimal128 {
+        precision: 4,
+        scale: 1,
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_evaluate_decimal_arithmetic() {
+    let evaluate = |left: ScalarValue, op: BinaryOperator, right: ScalarValue| {
+        binary(Expr::Literal(left), op, Expr::Literal(right)).evaluate(&schema(), &row(None))
+    };
+
+    // The scales are aligned before adding
+    let sum = evaluate(
+        decimal("1.5", 2, 1),
+        BinaryOperator::Plus,
+        decimal("2.25", 3, 2),
+    );
+    assert_eq!(sum.unwrap(), ScalarValue::Decimal128(Some(375), 4, 2));
+
+    let difference = evaluate(
+        decimal("1.5", 2, 1),
+        BinaryOperator::Minus,
+        decimal("2.25", 3, 2),
+    );
+    assert_eq!(difference.unwrap().to_string(), "-0.75");
+
+    let product = evaluate(
+        decimal("1.5", 2, 1),
+        BinaryOperator::Multiply,
+        decimal("2.25", 3, 2),
+    );
+    assert_eq!(product.unwrap(), ScalarValue::Decimal128(Some(3375), 6, 3));
+
+    let unknown = evaluate(
+        decimal("1.5", 2, 1),
+        BinaryOperator::Plus,
+        ScalarValue::Null,
+    );
+    assert_eq!(unknown.unwrap(), ScalarValue::Decimal128(None, 2, 1));
+
+    let comparison = evaluate(
+        decimal("1.5", 2, 1),
+        BinaryOperator::Lt,
+        decimal("1.50001", 6, 5),
+    );
+    assert_eq!(comparison.unwrap(), ScalarValue::Boolean(Some(true)));
+
+    // The result has more digits than the maximum precision
+    let max = ScalarValue::Decimal128(Some(10i128.pow(38) - 1), 38, 0);
+    let overflow = evaluate(max.clone(), BinaryOperator::Plus, decimal("1", 1, 0));
+    assert!(matches!(overflow, Err(Error::Execution(_))));
+    let overflow = evaluate(max.clone(), BinaryOperator::Multiply, max);
+    assert!(matches!(overflow, Err(Error::Execution(_))));
+}
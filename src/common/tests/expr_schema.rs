@@ -0,0 +1,181 @@
+use common::error::Error;
+use common::expr::{Alias, BinaryExpr, BinaryOperator, Case, Expr};
+use common::scalar::ScalarValue;
+use common::schema::{DataType, Field, Schema};
+use common::table_reference::TableReference;
+use common::testing::{col, lit};
+
+fn employees() -> TableReference {
+    TableReference::Bare {
+        table: "employees".to_string(),
+    }
+}
+
+fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false).with_relation(Some(employees())),
+        Field::new("name", DataType::Utf8, true).with_relation(Some(employees())),
+        Field::new("salary", DataType::Int64, true).with_relation(Some(employees())),
+    ])
+}
+
+fn plus(left: Expr, right: Expr) -> Expr {
+    Expr::BinaryExpr(BinaryExpr {
+        left: Box::new(left),
+        op: BinaryOperator::Plus,
+        right: Box::new(right),
+    })
+}
+
+#[test]
+fn test_column_field_keeps_its_name() {
+    let field = col("name").to_field(&schema()).unwrap();
+    assert_eq!(
+        field,
+        Field::new("name", DataType::Utf8, true).with_relation(Some(employees()))
+    );
+}
+
+#[test]
+fn test_alias_field_uses_alias_name() {
+    let expr = Expr::Alias(Alias {
+        expr: Box::new(col("id")),
+        name: "employee_id".to_string(),
+    });
+    let field = expr.to_field(&schema()).unwrap();
+    assert_eq!(field, Field::new("employee_id", DataType::Int64, false));
+}
+
+#[test]
+fn test_computed_field_uses_generated_name() {
+    let field = plus(col("salary"), lit(1000)).to_field(&schema()).unwrap();
+    assert_eq!(field, Field::new("salary + 1000", DataType::Int64, true));
+
+    // Non-nullable operands give a non-nullable result
+    let field = plus(col("id"), lit(1)).to_field(&schema()).unwrap();
+    assert_eq!(field, Field::new("id + 1", DataType::Int64, false));
+}
+
+#[test]
+fn test_unresolved_column_field() {
+    let result = col("bonus").to_field(&schema());
+    assert!(matches!(result, Err(Error::SchemaError(_))));
+}
+
+#[test]
+fn test_invalid_arithmetic_type() {
+    let result = plus(col("name"), lit(1)).to_field(&schema());
+    assert!(matches!(result, Err(Error::Plan(_))));
+}
+
+#[test]
+fn test_comparison_type() {
+    let compare = |left, right| {
+        Expr::BinaryExpr(BinaryExpr {
+            left: Box::new(left),
+            op: BinaryOperator::Lt,
+            right: Box::new(right),
+        })
+        .get_type(&schema())
+    };
+    assert_eq!(compare(col("id"), lit(1)).unwrap(), DataType::Boolean);
+    // Strings compare with dates, not with numbers
+    let date = Expr::Literal(ScalarValue::Date32(Some(0)));
+    assert_eq!(compare(col("name"), date).unwrap(), DataType::Boolean);
+    let decimal = Expr::Literal(ScalarValue::Decimal128(Some(150), 10, 2));
+    let result = compare(col("name"), decimal);
+    assert!(matches!(result, Err(Error::Plan(_))), "{result:?}");
+}
+
+#[test]
+fn test_nullable() {
+    let schema = schema();
+    let null = Expr::Literal(ScalarValue::Int64(None));
+
+    // Columns follow the schema, literals whether they are NULL
+    assert!(!col("id").nullable(&schema).unwrap());
+    assert!(col("salary").nullable(&schema).unwrap());
+    assert!(!lit(1).nullable(&schema).unwrap());
+    assert!(null.nullable(&schema).unwrap());
+    assert!(Expr::Literal(ScalarValue::Null).nullable(&schema).unwrap());
+
+    // Arithmetic is nullable if any operand is
+    assert!(!plus(col("id"), lit(1)).nullable(&schema).unwrap());
+    assert!(plus(col("id"), null.clone()).nullable(&schema).unwrap());
+    assert!(plus(col("salary"), lit(1)).nullable(&schema).unwrap());
+
+    // Null checks never are
+    assert!(
+        !Expr::IsNull(Box::new(col("salary")))
+            .nullable(&schema)
+            .unwrap()
+    );
+    assert!(!Expr::IsNotNull(Box::new(null)).nullable(&schema).unwrap());
+
+    // Aliases follow the aliased expression
+    let alias = Expr::Alias(Alias {
+        expr: Box::new(col("salary")),
+        name: "pay".to_string(),
+    });
+    assert!(alias.nullable(&schema).unwrap());
+
+    assert!(matches!(
+        col("bonus").nullable(&schema),
+        Err(Error::SchemaError(_))
+    ));
+}
+
+#[test]
+fn test_case_nullable() {
+    let schema = schema();
+    let case = |else_expr: Option<Expr>| {
+        Expr::Case(Case {
+            expr: None,
+            when_then_expr: vec![(
+                Box::new(Expr::IsNull(Box::new(col("salary")))),
+                Box::new(lit(0)),
+            )],
+            else_expr: else_expr.map(Box::new),
+        })
+    };
+
+    // No ELSE means an unmatched row yields NULL
+    assert!(case(None).nullable(&schema).unwrap());
+    assert!(!case(Some(col("id"))).nullable(&schema).unwrap());
+    assert!(case(Some(col("salary"))).nullable(&schema).unwrap());
+}
+
+#[test]
+fn test_coalesce_type() {
+    let schema = schema();
+    let null = Expr::Literal(ScalarValue::Null);
+
+    // Untyped NULLs adopt the type of the other arguments
+    let expr = Expr::Coalesce(vec![null.clone(), col("salary"), lit(0)]);
+    assert_eq!(expr.get_type(&schema).unwrap(), DataType::Int64);
+    assert_eq!(
+        Expr::Coalesce(vec![null]).get_type(&schema).unwrap(),
+        DataType::Null
+    );
+
+    let expr = Expr::Coalesce(vec![col("salary"), col("name")]);
+    assert!(matches!(expr.get_type(&schema), Err(Error::Plan(_))));
+}
+
+#[test]
+fn test_coalesce_nullable() {
+    let schema = schema();
+    let expr = Expr::Coalesce(vec![col("salary"), col("id")]);
+    assert!(!expr.nullable(&schema).unwrap());
+
+    let expr = Expr::Coalesce(vec![col("salary"), Expr::Literal(ScalarValue::Null)]);
+    assert!(expr.nullable(&schema).unwrap());
+
+    let field = Expr::Coalesce(vec![col("salary"), lit(0)])
+        .to_field(&schema)
+        .unwrap();
+    assert_eq!(
+        field,
+        Field::new("coalesce(salary, 0)", DataType::Int64, false)
+    );
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use common::column::Column;
+use common::error::Result;
+use common::expr::{BinaryOperator, Case, Expr};
+use common::expr_visitor::ExprVisitor;
+use common::scalar::ScalarValue;
+use common::table_reference::TableReference;
+use common::testing::{binary, col, lit, qualified_col};
+use common::tree_node::TreeNodeRecursion;
+
+/// CASE WHEN salary > 1 THEN 2 ELSE coalesce(bonus, 3) END + 4
+fn example_expr() -> Expr {
+    let case = Expr::Case(Case {
+        expr: None,
+        when_then_expr: vec![(
+            Box::new(binary(col("salary"), BinaryOperator::Gt, lit(1))),
+            Box::new(lit(2)),
+        )],
+        else_expr: Some(Box::new(Expr::Coalesce(vec![col("bonus"), lit(3)]))),
+    });
+    binary(case, BinaryOperator::Plus, lit(4))
+}
+
+/// Collects every literal, optionally stopping after `limit` of them.
+#[derive(Default)]
+struct LiteralCollector {
+    literals: Vec<ScalarValue>,
+    limit: Option<usize>,
+}
+
+impl ExprVisitor for LiteralCollector {
+    fn f_down(&mut self, expr: &Expr) -> Result<TreeNodeRecursion> {
+        if let Expr::Literal(value) = expr {
+            self.literals.push(value.clone());
+            if Some(self.literals.len()) == self.limit {
+                return Ok(TreeNodeRecursion::Stop);
+            }
+        }
+        Ok(TreeNodeRecursion::Continue)
+    }
+}
+
+#[test]
+fn test_visitor_collects_literals_in_order() {
+    let mut visitor = LiteralCollector::default();
+    let recursion = example_expr().accept(&mut visitor).unwrap();
+
+    assert_eq!(recursion, TreeNodeRecursion::Continue);
+    let expected: Vec<_> = (1..=4).map(|n| ScalarValue::Int64(Some(n))).collect();
+    assert_eq!(visitor.literals, expected);
+}
+
+#[test]
+fn test_visitor_stop_ends_traversal() {
+    let mut visitor = LiteralCollector {
+        limit: Some(2),
+        ..Default::default()
+    };
+    let recursion = example_expr().accept(&mut visitor).unwrap();
+
+    assert_eq!(recursion, TreeNodeRecursion::Stop);
+    assert_eq!(
+        visitor.literals,
+        vec![ScalarValue::Int64(Some(1)), ScalarValue::Int64(Some(2))]
+    );
+}
+
+/// Records the pre- and post-order visits, skipping the children of CASE.
+#[derive(Default)]
+struct Tracer {
+    events: Vec<String>,
+}
+
+impl ExprVisitor for Tracer {
+    fn f_down(&mut self, expr: &Expr) -> Result<TreeNodeRecursion> {
+        self.events.push(format!("down {expr}"));
+        match expr {
+            Expr::Case(_) => Ok(TreeNodeRecursion::Jump),
+            _ => Ok(TreeNodeRecursion::Continue),
+        }
+    }
+
+    fn f_up(&mut self, expr: &Expr) -> Result<TreeNodeRecursion> {
+        self.events.push(format!("up {expr}"));
+        Ok(TreeNodeRecursion::Continue)
+    }
+}
+
+#[test]
+fn test_visitor_jump_skips_children() {
+    let case = Expr::Case(Case {
+        expr: None,
+        when_then_expr: vec![(Box::new(col("a")), Box::new(lit(1)))],
+        else_expr: None,
+    });
+    let expr = binary(case.clone(), BinaryOperator::Plus, lit(2));
+
+    let mut visitor = Tracer::default();
+    expr.accept(&mut visitor).unwrap();
+
+    let expected = vec![
+        format!("down {expr}"),
+        format!("down {case}"),
+        format!("up {case}"),
+        "down 2".to_string(),
+        "up 2".to_string(),
+        format!("up {expr}"),
+    ];
+    assert_eq!(visitor.events, expected);
+}
+
+#[test]
+fn test_references_only() {
+    let column = |table: &str, name: &str| Column::new(Some(TableReference::bare(table)), name);
+    let left: HashSet<Column> = [column("a", "id"), column("a", "salary")].into();
+    let a_id = qualified_col("a", "id");
+    let a_salary = qualified_col("a", "salary");
+    let b_id = qualified_col("b", "id");
+
+    let left_only = binary(
+        binary(a_salary, BinaryOperator::Gt, lit(100)),
+        BinaryOperator::And,
+        Expr::IsNotNull(Box::new(a_id.clone())),
+    );
+    assert!(left_only.references_only(&left));
+
+    let both_sides = binary(a_id, BinaryOperator::Eq, b_id);
+    assert!(!both_sides.references_only(&left));
+    assert_eq!(
+        both_sides.column_refs(),
+        [column("a", "id"), column("b", "id")].into()
+    );
+
+    // No columns at all
+    assert!(lit(1).references_only(&HashSet::new()));
+}
+
+#[test]
+fn test_depth_and_size() {
+    assert_eq!(lit(1).depth(), 1);
+    assert_eq!(lit(1).size(), 1);
+
+    let expr = example_expr();
+    assert_eq!(expr.depth(), 4);
+    assert_eq!(expr.size(), 10);
+}
+
+#[test]
+fn test_may_fail() {
+    assert!(example_expr().may_fail());
+    assert!(!binary(col("salary"), BinaryOperator::Gt, lit(1)).may_fail());
+    assert!(!Expr::Coalesce(vec![col("bonus"), lit(3)]).may_fail());
+    let ratio = binary(lit(10), BinaryOperator::Divide, col("id"));
+    assert!(binary(ratio, BinaryOperator::Gt, lit(1)).may_fail());
+}
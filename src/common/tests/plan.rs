@@ -0,0 +1,374 @@
+use std::sync::Arc;
+
+use common::error::Error;
+use common::expr::Expr;
+use common::plan::{
+    Aggregate, Filter, Join, JoinType, Limit, LogicalPlan, Projection, Sort, SortExpr, TableScan,
+};
+use common::schema::{DataType, Field, Schema};
+use common::table_reference::TableReference;
+use common::testing::{alias, col};
+use common::tree_node::{Transformed, TreeNode, TreeNodeRecursion};
+
+fn scan(table_name: &str, projected_columns: &[&str]) -> LogicalPlan {
+    LogicalPlan::TableScan(TableScan {
+        table_name: table_name.to_string(),
+        table_schema: Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]),
+        projected_columns: projected_columns.iter().map(ToString::to_string).collect(),
+    })
+}
+
+fn field(table: &str, name: &str, data_type: DataType, nullable: bool) -> Field {
+    Field::new(name, data_type, nullable).with_relation(Some(TableReference::bare(table)))
+}
+
+fn aggregate(input: LogicalPlan) -> LogicalPlan {
+    LogicalPlan::Aggregate(Aggregate {
+        input: Arc::new(input),
+        group_expr: vec![col("name")],
+        aggr_expr: vec![alias(Expr::IsNull(Box::new(col("id"))), "missing")],
+    })
+}
+
+fn sort(input: LogicalPlan) -> LogicalPlan {
+    LogicalPlan::Sort(Sort {
+        expr: vec![
+            SortExpr::new(col("name"), true, false),
+            SortExpr::new(col("id"), false, true),
+        ],
+        input: Arc::new(input),
+    })
+}
+
+#[test]
+fn test_table_scan_schema() {
+    let schema = scan("a", &["name", "id"]).schema().unwrap();
+    assert_eq!(
+        schema,
+        Schema::new(vec![
+            field("a", "name", DataType::Utf8, true),
+            field("a", "id", DataType::Int64, false),
+        ])
+    );
+
+    let result = scan("a", &["salary"]).schema();
+    assert!(matches!(result, Err(Error::SchemaError(_))));
+}
+
+#[test]
+fn test_left_join_schema() {
+    let plan = LogicalPlan::Join(Join {
+        left: Arc::new(scan("a", &["id"])),
+        right: Arc::new(scan("b", &["id"])),
+        on: vec![("id".to_string(), "id".to_string())],
+        join_type: JoinType::Left,
+    });
+    assert_eq!(
+        plan.schema().unwrap(),
+        Schema::new(vec![
+            field("a", "id", DataType::Int64, false),
+            field("b", "id", DataType::Int64, true),
+        ])
+    );
+}
+
+#[test]
+fn test_projection_try_new() {
+    let input = Arc::new(scan("a", &["id", "name"]));
+    let projection =
+        Projection::try_new(vec![col("name"), alias(col("id"), "key")], input.clone()).unwrap();
+    assert_eq!(
+        LogicalPlan::Projection(projection).schema().unwrap(),
+        Schema::new(vec![
+            field("a", "name", DataType::Utf8, true),
+            Field::new("key", DataType::Int64, false),
+        ])
+    );
+
+    // Duplicates are fine once aliased apart
+    Projection::try_new(vec![col("id"), alias(col("id"), "id2")], input).unwrap();
+}
+
+#[test]
+fn test_projection_try_new_unresolved_column() {
+    let input = Arc::new(scan("a", &["id"]));
+    let result = Projection::try_new(vec![col("name")], input);
+    assert!(matches!(result, Err(Error::SchemaError(_))));
+}
+
+#[test]
+fn test_projection_try_new_duplicate_names() {
+    let input = Arc::new(scan("a", &["id", "name"]));
+    let result = Projection::try_new(vec![col("id"), alias(col("name"), "id")], input.clone());
+    assert!(matches!(result, Err(Error::SchemaError(_))));
+
+    let result = Projection::try_new(vec![col("id"), col("id")], input);
+    assert!(matches!(result, Err(Error::SchemaError(_))));
+}
+
+#[test]
+fn test_output_names() {
+    let projection = Projection::try_new(
+        vec![col("name"), alias(col("id"), "key")],
+        Arc::new(scan("a", &["id", "name"])),
+    )
+    .unwrap();
+    assert_eq!(
+        LogicalPlan::Projection(projection).output_names().unwrap(),
+        vec!["name".to_string(), "key".to_string()]
+    );
+}
+
+#[test]
+fn test_map_expressions() {
+    let input = Arc::new(LogicalPlan::Filter(Filter {
+        predicate: Expr::IsNotNull(Box::new(col("name"))),
+        input: Arc::new(scan("a", &["id", "name"])),
+    }));
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![col("id"), col("name")],
+        input: input.clone(),
+    });
+
+    let mut visited = vec![];
+    let result = plan
+        .map_expressions(|expr| {
+            visited.push(expr.to_string());
+            Ok(Transformed::yes(Expr::IsNull(Box::new(expr))))
+        })
+        .unwrap();
+
+    // The filter below the projection is left alone
+    assert_eq!(visited, vec!["id", "name"]);
+    assert!(result.was_transformed());
+    let LogicalPlan::Projection(projection) = result.into_inner() else {
+        panic!("expected a projection");
+    };
+    assert_eq!(
+        projection.expr,
+        vec![
+            Expr::IsNull(Box::new(col("id"))),
+            Expr::IsNull(Box::new(col("name")))
+        ]
+    );
+    assert!(Arc::ptr_eq(&projection.input, &input));
+
+    // Nodes without expressions are unchanged
+    let result = scan("a", &["id"])
+        .map_expressions(|expr| Ok(Transformed::yes(expr)))
+        .unwrap();
+    assert!(!result.was_transformed());
+}
+
+#[test]
+fn test_apply_expressions() {
+    let filter = LogicalPlan::Filter(Filter {
+        predicate: Expr::IsNotNull(Box::new(col("name"))),
+        input: Arc::new(scan("a", &["id", "name"])),
+    });
+    let join = LogicalPlan::Join(Join {
+        left: Arc::new(filter.clone()),
+        right: Arc::new(scan("b", &["id"])),
+        on: vec![("id".to_string(), "id".to_string())],
+        join_type: JoinType::Inner,
+    });
+    let projection = LogicalPlan::Projection(Projection {
+        expr: vec![col("id"), col("name"), col("id")],
+        input: Arc::new(join.clone()),
+    });
+
+    let count = |plan: &LogicalPlan| {
+        let mut count = 0;
+        plan.apply_expressions(|_| {
+            count += 1;
+            Ok(TreeNodeRecursion::Continue)
+        })
+        .unwrap();
+        count
+    };
+    assert_eq!(count(&filter), 1);
+    // Join conditions are column names, not expressions
+    assert_eq!(count(&join), 0);
+    assert_eq!(count(&projection), 3);
+
+    let mut visited = vec![];
+    let recursion = projection
+        .apply_expressions(|expr| {
+            visited.push(expr.to_string());
+            Ok(if visited.len() == 2 {
+                TreeNodeRecursion::Stop
+            } else {
+                TreeNodeRecursion::Continue
+            })
+        })
+        .unwrap();
+    assert_eq!(recursion, TreeNodeRecursion::Stop);
+    assert_eq!(visited, vec!["id", "name"]);
+}
+
+#[test]
+fn test_inputs_and_expressions() {
+    let left = Arc::new(scan("a", &["id", "name"]));
+    let right = Arc::new(scan("b", &["id"]));
+    let join = LogicalPlan::Join(Join {
+        left: left.clone(),
+        right: right.clone(),
+        on: vec![("id".to_string(), "id".to_string())],
+        join_type: JoinType::Inner,
+    });
+    assert_eq!(join.inputs(), vec![&left, &right]);
+    assert!(join.expressions().is_empty());
+
+    let join = Arc::new(join);
+    let projection = LogicalPlan::Projection(Projection {
+        expr: vec![col("name"), alias(col("id"), "key")],
+        input: join.clone(),
+    });
+    assert_eq!(projection.inputs(), vec![&join]);
+    assert_eq!(
+        projection.expressions(),
+        vec![col("name"), alias(col("id"), "key")]
+    );
+    assert!(left.inputs().is_empty());
+}
+
+#[test]
+fn test_display_with_schema() {
+    let join = LogicalPlan::Join(Join {
+        left: Arc::new(scan("a", &["id", "name"])),
+        right: Arc::new(scan("b", &["id"])),
+        on: vec![("id".to_string(), "id".to_string())],
+        join_type: JoinType::Left,
+    });
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![
+            alias(col("name"), "a_name"),
+            Expr::IsNull(Box::new(col("b.id"))),
+        ],
+        input: Arc::new(join),
+    });
+
+    let expected = "\
+Projection: name AS a_name, b.id IS NULL [a_name:Utf8?, b.id IS NULL:Boolean]
+  Left Join: id = id [id:Int64, name:Utf8?, id:Int64?]
+    TableScan: a [id:Int64, name:Utf8?]
+    TableScan: b [id:Int64]
+";
+    assert_eq!(plan.display_with_schema(), expected);
+}
+
+#[test]
+fn test_aggregate_schema_and_display() {
+    let plan = aggregate(scan("a", &["id", "name"]));
+    assert_eq!(plan.output_names().unwrap(), ["name", "missing"]);
+    assert_eq!(plan.expressions().len(), 2);
+    let expected = "\
+Aggregate: groupBy=[name], aggr=[id IS NULL AS missing] [name:Utf8?, missing:Boolean]
+  TableScan: a [id:Int64, name:Utf8?]
+";
+    assert_eq!(plan.display_with_schema(), expected);
+}
+
+#[test]
+fn test_aggregate_tree_node() {
+    let plan = aggregate(scan("a", &["id", "name"]));
+    let result = plan
+        .transform(|node| match node {
+            LogicalPlan::TableScan(scan) => {
+                Ok(Transformed::yes(LogicalPlan::TableScan(TableScan {
+                    table_name: "b".to_string(),
+                    ..scan.clone()
+                })))
+            }
+            _ => Ok(Transformed::no(node.clone())),
+        })
+        .unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(result.into_inner(), aggregate(scan("b", &["id", "name"])));
+
+    // Stopping at the grouping expressions leaves the aggregates as they are
+    let result = plan
+        .map_expressions(|expr| {
+            Ok(Transformed::new(
+                alias(expr, "renamed"),
+                true,
+                TreeNodeRecursion::Stop,
+            ))
+        })
+        .unwrap();
+    let LogicalPlan::Aggregate(Aggregate {
+        group_expr,
+        aggr_expr,
+        ..
+    }) = result.into_inner()
+    else {
+        panic!("expected an aggregate");
+    };
+    assert_eq!(group_expr, vec![alias(col("name"), "renamed")]);
+    assert_eq!(
+        aggr_expr,
+        vec![alias(Expr::IsNull(Box::new(col("id"))), "missing")]
+    );
+}
+
+#[test]
+fn test_sort_schema_and_display() {
+    let plan = sort(scan("a", &["id", "name"]));
+    assert_eq!(
+        plan.schema().unwrap(),
+        scan("a", &["id", "name"]).schema().unwrap()
+    );
+    assert_eq!(plan.expressions(), vec![col("name"), col("id")]);
+    let expected = "\
+Sort: name ASC NULLS LAST, id DESC NULLS FIRST [id:Int64, name:Utf8?]
+  TableScan: a [id:Int64, name:Utf8?]
+";
+    assert_eq!(plan.display_with_schema(), expected);
+    plan.validate().unwrap();
+
+    let plan = LogicalPlan::Sort(Sort {
+        expr: vec![SortExpr::new(col("salary"), true, false)],
+        input: Arc::new(scan("a", &["id"])),
+    });
+    assert!(plan.validate().is_err());
+}
+
+#[test]
+fn test_sort_tree_node() {
+    let plan = sort(scan("a", &["id", "name"]));
+    let result = plan
+        .clone()
+        .map_children(|child| {
+            Ok(Transformed::yes(LogicalPlan::Limit(Limit {
+                fetch: 1,
+                input: Arc::new(child),
+            })))
+        })
+        .unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner(),
+        sort(LogicalPlan::Limit(Limit {
+            fetch: 1,
+            input: Arc::new(scan("a", &["id", "name"])),
+        }))
+    );
+
+    // Rewriting the expressions keeps each one's direction and null ordering
+    let result = plan
+        .map_expressions(|expr| Ok(Transformed::yes(Expr::IsNull(Box::new(expr)))))
+        .unwrap();
+    let LogicalPlan::Sort(Sort { expr, .. }) = result.into_inner() else {
+        panic!("expected a sort");
+    };
+    assert_eq!(
+        expr,
+        vec![
+            SortExpr::new(Expr::IsNull(Box::new(col("name"))), true, false),
+            SortExpr::new(Expr::IsNull(Box::new(col("id"))), false, true),
+        ]
+    );
+}
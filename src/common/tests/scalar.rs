@@ -0,0 +1,22 @@
+use common::scalar::ScalarValue;
+
+#[test]
+fn test_debug_typed() {
+    assert_eq!(
+        ScalarValue::Int64(Some(50000)).debug_typed(),
+        "Int64(50000)"
+    );
+    assert_eq!(ScalarValue::Utf8(None).debug_typed(), "Utf8(NULL)");
+    assert_eq!(ScalarValue::Int32(None).debug_typed(), "Int32(NULL)");
+    assert_eq!(ScalarValue::Null.debug_typed(), "NULL");
+    assert_eq!(
+        ScalarValue::Utf8(Some("Bill".to_string())).debug_typed(),
+        "Utf8('Bill')"
+    );
+    assert_eq!(
+        ScalarValue::Decimal128(Some(150), 10, 2).debug_typed(),
+        "Decimal128(1.50, 10, 2)"
+    );
+    // Display doesn't tell the NULLs apart
+    assert_eq!(ScalarValue::Utf8(None).to_string(), "NULL");
+}
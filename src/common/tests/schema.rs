@@ -0,0 +1,164 @@
+use common::column::Column;
+use common::config::SessionConfig;
+use common::error::Error;
+use common::plan::JoinType;
+use common::schema::{DataType, ESTIMATED_VARIABLE_WIDTH, Field, Schema};
+use common::table_reference::TableReference;
+
+fn schema() -> Schema {
+    let employees = Some(TableReference::Bare {
+        table: "employees".to_string(),
+    });
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false).with_relation(employees.clone()),
+        Field::new("Salary", DataType::Int64, true).with_relation(employees),
+    ])
+}
+
+fn table(name: &str, fields: &[&str]) -> Schema {
+    let relation = Some(TableReference::bare(name));
+    Schema::new(
+        fields
+            .iter()
+            .map(|field| Field::new(*field, DataType::Int64, false).with_relation(relation.clone()))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_case_insensitive_identifiers() {
+    let config = SessionConfig::new();
+    let schema = schema();
+
+    for column in [
+        Column::new_unqualified("salary"),
+        Column::new_unqualified("SALARY"),
+        Column::new(Some(TableReference::bare("EMPLOYEES")), "salary"),
+    ] {
+        let field = schema.qualified_field(&column, &config).unwrap();
+        assert_eq!(field.name, "Salary");
+    }
+}
+
+#[test]
+fn test_case_sensitive_identifiers() {
+    let config = SessionConfig::new().with_case_sensitive_identifiers(true);
+    let schema = schema();
+
+    let field = schema
+        .qualified_field(
+            &Column::new(Some(TableReference::bare("employees")), "Salary"),
+            &config,
+        )
+        .unwrap();
+    assert_eq!(field.name, "Salary");
+
+    for column in [
+        Column::new_unqualified("salary"),
+        Column::new(Some(TableReference::bare("EMPLOYEES")), "Salary"),
+    ] {
+        assert!(matches!(
+            schema.qualified_field(&column, &config),
+            Err(Error::SchemaError(_))
+        ));
+    }
+}
+
+#[test]
+fn test_case_insensitive_ambiguity() {
+    let schema = Schema::new(vec![
+        Field::new("salary", DataType::Int64, true),
+        Field::new("SALARY", DataType::Int64, true),
+    ]);
+    let result = schema.qualified_field(&Column::new_unqualified("Salary"), &SessionConfig::new());
+    assert!(matches!(result, Err(Error::SchemaError(_))));
+
+    let config = SessionConfig::new().with_case_sensitive_identifiers(true);
+    let field = schema
+        .qualified_field(&Column::new_unqualified("SALARY"), &config)
+        .unwrap();
+    assert_eq!(field.name, "SALARY");
+}
+
+#[test]
+fn test_project() {
+    let schema = schema();
+    let projected = schema.project(&[1, 0]).unwrap();
+    assert_eq!(
+        projected.fields,
+        vec![schema.fields[1].clone(), schema.fields[0].clone()]
+    );
+    assert_eq!(schema.project(&[]).unwrap(), Schema::empty());
+
+    let result = schema.project(&[0, 2]);
+    assert!(matches!(result, Err(Error::SchemaError(_))));
+}
+
+#[test]
+fn test_project_by_name() {
+    let schema = schema();
+    let projected = schema.project_by_name(&["Salary", "id"]).unwrap();
+    assert_eq!(projected, schema.project(&[1, 0]).unwrap());
+
+    let result = schema.project_by_name(&["salary"]);
+    assert!(matches!(result, Err(Error::SchemaError(_))));
+}
+
+#[test]
+fn test_estimated_row_width() {
+    assert_eq!(DataType::Int64.byte_width(), Some(8));
+    assert_eq!(DataType::Date32.byte_width(), Some(4));
+    assert_eq!(DataType::Utf8.byte_width(), None);
+
+    let schema = Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("active", DataType::Boolean, true),
+        Field::new("hired", DataType::Date32, true),
+        Field::new("salary", DataType::Float64, true),
+        Field::new("name", DataType::Utf8, true),
+    ]);
+    assert_eq!(
+        schema.estimated_row_width(),
+        8 + 1 + 4 + 8 + ESTIMATED_VARIABLE_WIDTH
+    );
+}
+
+#[test]
+fn test_join_schema() {
+    let employees = table("employees", &["id", "dept_id"]);
+    let departments = table("departments", &["id", "budget"]);
+
+    let joined = employees.join(&departments, JoinType::Inner).unwrap();
+    let names: Vec<_> = joined
+        .fields
+        .iter()
+        .map(|field| field.column().to_string())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "employees.id",
+            "employees.dept_id",
+            "departments.id",
+            "departments.budget"
+        ]
+    );
+    assert!(joined.fields.iter().all(|field| !field.nullable));
+
+    let joined = employees.join(&departments, JoinType::Left).unwrap();
+    let nullable: Vec<_> = joined.fields.iter().map(|field| field.nullable).collect();
+    assert_eq!(nullable, [false, false, true, true]);
+}
+
+#[test]
+fn test_join_schema_unqualified_collision() {
+    let unqualified = Schema::new(vec![Field::new("id", DataType::Int64, false)]);
+    assert!(matches!(
+        unqualified.join(&unqualified, JoinType::Inner),
+        Err(Error::SchemaError(_))
+    ));
+    // A qualifier on either side tells them apart
+    unqualified
+        .join(&table("departments", &["id"]), JoinType::Inner)
+        .unwrap();
+}
@@ -0,0 +1,47 @@
+use common::config::SessionConfig;
+use common::table_reference::TableReference;
+
+#[test]
+fn test_parse_dotted_reference() {
+    assert_eq!(
+        TableReference::parse("catalog.schema.table").unwrap(),
+        TableReference::full("catalog", "schema", "table")
+    );
+    assert_eq!(
+        TableReference::parse("hr.employees").unwrap(),
+        TableReference::partial("hr", "employees")
+    );
+    assert_eq!(
+        TableReference::parse("\"my.db\".\"Emp\"\"s\"").unwrap(),
+        TableReference::partial("my.db", "Emp\"s")
+    );
+    assert!(TableReference::parse("a..b").is_err());
+    assert!(TableReference::parse("\"unterminated").is_err());
+    assert!(TableReference::parse("a.b.c.d").is_err());
+}
+
+#[test]
+fn test_parse_normalized() {
+    let config = SessionConfig::new();
+    assert_eq!(
+        TableReference::parse_normalized("HR.\"Employees\"", &config).unwrap(),
+        TableReference::partial("hr", "Employees")
+    );
+    let config = config.with_case_sensitive_identifiers(true);
+    assert_eq!(
+        TableReference::parse_normalized("HR.Employees", &config).unwrap(),
+        TableReference::partial("HR", "Employees")
+    );
+}
+
+#[test]
+fn test_bare_reference_matches_qualified() {
+    let bare = TableReference::bare("employees");
+    let full = TableReference::full("company", "hr", "employees");
+    assert!(bare.resolved_eq(&full));
+    assert!(full.resolved_eq(&bare));
+    assert!(TableReference::partial("hr", "employees").resolved_eq(&full));
+    assert!(!TableReference::partial("sales", "employees").resolved_eq(&full));
+    assert!(!TableReference::bare("salaries").resolved_eq(&full));
+    assert_eq!(full.to_string(), "company.hr.employees");
+}
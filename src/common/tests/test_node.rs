@@ -1,446 +1,290 @@
-use std::fmt;
 use std::sync::Arc;
 
-// Result type for transformations
-#[derive(Debug, Clone)]
-pub enum Transformed<T> {
-    Yes(T), // Node was transformed
-    No(T),  // Node was not transformed
+use common::error::{Error, Result};
+use common::expr::{BinaryOperator, Expr};
+use common::plan::{Filter, JoinType, Limit, LogicalPlan, TableScan};
+use common::scalar::ScalarValue;
+use common::schema::{DataType, Field, Schema};
+use common::testing::{binary, col, filter, join, lit};
+use common::tree_node::{Transformed, TreeNode, TreeNodeRecursion, TreeNodeRewriter};
+
+fn employees_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("salary", DataType::Int64, true),
+    ])
 }
 
-impl<T> Transformed<T> {
-    pub fn into_inner(self) -> T {
-        match self {
-            Transformed::Yes(t) | Transformed::No(t) => t,
-        }
-    }
-
-    pub fn was_transformed(&self) -> bool {
-        matches!(self, Transformed::Yes(_))
-    }
+fn scan(table_name: &str) -> LogicalPlan {
+    LogicalPlan::TableScan(TableScan {
+        table_name: table_name.to_string(),
+        table_schema: employees_schema(),
+        projected_columns: vec!["id".to_string(), "name".to_string(), "salary".to_string()],
+    })
 }
 
-// TreeNode trait - core abstraction for tree traversal and transformation
-pub trait TreeNode: Sized {
-    /// Apply a function to all children of this node
-    fn apply_children<F>(&self, f: F) -> Result<Transformed<Self>, String>
-    where
-        F: Fn(&Self) -> Result<Transformed<Self>, String>;
-
-    /// Transform this node by applying a function to all its children first
-    fn map_children<F>(self, f: F) -> Result<Transformed<Self>, String>
-    where
-        F: Fn(Self) -> Result<Transformed<Self>, String>;
-
-    /// Apply a transformation function to this node and all its descendants (post-order)
-    fn transform<F>(&self, f: F) -> Result<Transformed<Self>, String>
-    where
-        F: Fn(&Self) -> Result<Transformed<Self>, String>,
-    {
-        // First, recursively transform all children
-        let transformed_children = self.apply_children(|node| node.transform(&f))?;
+/// Limit -> Filter(id < 1000) -> Filter(salary > 50000) -> TableScan
+fn nested_filters() -> LogicalPlan {
+    let filter1 = filter(
+        binary(col("salary"), BinaryOperator::Gt, lit(50000)),
+        scan("employees"),
+    );
+    let filter2 = filter(binary(col("id"), BinaryOperator::Lt, lit(1000)), filter1);
+    LogicalPlan::Limit(Limit {
+        fetch: 10,
+        input: Arc::new(filter2),
+    })
+}
 
-        // Then apply the transformation to this node
-        let node = transformed_children.into_inner();
-        f(&node)
+fn rename_scan(plan: &LogicalPlan, from: &str, to: &str) -> Option<LogicalPlan> {
+    match plan {
+        LogicalPlan::TableScan(scan) if scan.table_name == from => {
+            Some(LogicalPlan::TableScan(TableScan {
+                table_name: to.to_string(),
+                ..scan.clone()
+            }))
+        }
+        _ => None,
     }
+}
 
-    /// Apply a transformation function that can mutate the tree (consumes self)
-    fn transform_down<F>(self, f: F) -> Result<Transformed<Self>, String>
-    where
-        F: Fn(Self) -> Result<Transformed<Self>, String>,
-    {
-        // Apply transformation to this node first (pre-order)
-        let transformed_node = f(self)?;
-
-        // Then recursively transform children
-        let node = transformed_node.into_inner();
-        node.map_children(|child| child.transform_down(&f))
+/// Replace every filter predicate with `true`.
+fn drop_predicate(plan: LogicalPlan) -> LogicalPlan {
+    match plan {
+        LogicalPlan::Filter(Filter { input, .. }) => LogicalPlan::Filter(Filter {
+            predicate: Expr::Literal(ScalarValue::Boolean(Some(true))),
+            input,
+        }),
+        plan => plan,
     }
 }
 
-// Example logical plan nodes
-#[derive(Debug, Clone)]
-pub enum LogicalPlan {
-    TableScan {
-        table_name: String,
-        projected_columns: Vec<String>,
-    },
-    Filter {
-        predicate: Expression,
-        input: Arc<LogicalPlan>,
-    },
-    Projection {
-        expressions: Vec<Expression>,
-        input: Arc<LogicalPlan>,
-    },
-    Join {
-        left: Arc<LogicalPlan>,
-        right: Arc<LogicalPlan>,
-        join_type: JoinType,
-        on: Vec<(String, String)>,
-    },
-    Limit {
-        limit: usize,
-        input: Arc<LogicalPlan>,
-    },
-}
+#[test]
+fn test_transformed_constructors() {
+    let yes = Transformed::yes(1);
+    assert!(yes.was_transformed());
+    assert_eq!(yes.tnode_recursion, TreeNodeRecursion::Continue);
 
-#[derive(Debug, Clone)]
-pub enum JoinType {
-    Inner,
-    Left,
-    Right,
-    Full,
-}
+    let no = Transformed::no(1);
+    assert!(!no.was_transformed());
+    assert_eq!(no.tnode_recursion, TreeNodeRecursion::Continue);
 
-#[derive(Debug, Clone)]
-pub enum Expression {
-    Column(String),
-    Literal(i64),
-    BinaryOp {
-        left: Box<Expression>,
-        op: BinaryOperator,
-        right: Box<Expression>,
-    },
-    IsNull(Box<Expression>),
+    let mapped = Transformed::new(1, true, TreeNodeRecursion::Stop).map_data(|n| n + 1);
+    assert_eq!(mapped, Transformed::new(2, true, TreeNodeRecursion::Stop));
 }
 
-#[derive(Debug, Clone)]
-pub enum BinaryOperator {
-    Eq,
-    Ne,
-    Lt,
-    Le,
-    Gt,
-    Ge,
-    And,
-    Or,
-    Plus,
-    Minus,
+#[test]
+fn test_transform_reports_changed_children() {
+    let plan = join(scan("a"), scan("b"), JoinType::Inner);
+    let result = plan
+        .transform(|node| match rename_scan(node, "b", "c") {
+            Some(renamed) => Ok(Transformed::yes(renamed)),
+            None => Ok(Transformed::no(node.clone())),
+        })
+        .unwrap();
+
+    // The root itself was not rewritten, but a descendant was
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner(),
+        join(scan("a"), scan("c"), JoinType::Inner)
+    );
 }
 
-// Implement TreeNode for LogicalPlan
-impl TreeNode for LogicalPlan {
-    fn apply_children<F>(&self, f: F) -> Result<Transformed<Self>, String>
-    where
-        F: Fn(&Self) -> Result<Transformed<Self>, String>,
-    {
-        match self {
-            LogicalPlan::TableScan { .. } => {
-                // Leaf node - no children to transform
-                Ok(Transformed::No(self.clone()))
-            }
-            LogicalPlan::Filter { predicate, input } => {
-                let transformed_input = f(input)?;
-                if transformed_input.was_transformed() {
-                    Ok(Transformed::Yes(LogicalPlan::Filter {
-                        predicate: predicate.clone(),
-                        input: Arc::new(transformed_input.into_inner()),
-                    }))
-                } else {
-                    Ok(Transformed::No(self.clone()))
-                }
+#[test]
+fn test_transform_stop_skips_siblings_and_ancestors() {
+    let plan = join(scan("a"), scan("b"), JoinType::Inner);
+    let result = plan
+        .transform(|node| {
+            if let Some(renamed) = rename_scan(node, "a", "x") {
+                return Ok(Transformed::new(renamed, true, TreeNodeRecursion::Stop));
             }
-            LogicalPlan::Projection { expressions, input } => {
-                let transformed_input = f(input)?;
-                if transformed_input.was_transformed() {
-                    Ok(Transformed::Yes(LogicalPlan::Projection {
-                        expressions: expressions.clone(),
-                        input: Arc::new(transformed_input.into_inner()),
-                    }))
-                } else {
-                    Ok(Transformed::No(self.clone()))
-                }
-            }
-            LogicalPlan::Join {
-                left,
-                right,
-                join_type,
-                on,
-            } => {
-                let transformed_left = f(left)?;
-                let transformed_right = f(right)?;
-
-                if transformed_left.was_transformed() || transformed_right.was_transformed() {
-                    Ok(Transformed::Yes(LogicalPlan::Join {
-                        left: Arc::new(transformed_left.into_inner()),
-                        right: Arc::new(transformed_right.into_inner()),
-                        join_type: join_type.clone(),
-                        on: on.clone(),
-                    }))
-                } else {
-                    Ok(Transformed::No(self.clone()))
-                }
-            }
-            LogicalPlan::Limit { limit, input } => {
-                let transformed_input = f(input)?;
-                if transformed_input.was_transformed() {
-                    Ok(Transformed::Yes(LogicalPlan::Limit {
-                        limit: *limit,
-                        input: Arc::new(transformed_input.into_inner()),
-                    }))
-                } else {
-                    Ok(Transformed::No(self.clone()))
-                }
-            }
-        }
-    }
-
-    fn map_children<F>(self, f: F) -> Result<Transformed<Self>, String>
-    where
-        F: Fn(Self) -> Result<Transformed<Self>, String>,
-    {
-        match self {
-            LogicalPlan::TableScan { .. } => {
-                // Leaf node - no children to transform
-                Ok(Transformed::No(self))
-            }
-            LogicalPlan::Filter { predicate, input } => {
-                let input_plan = Arc::try_unwrap(input).unwrap_or_else(|arc| (*arc).clone());
-                let transformed_input = f(input_plan)?;
-                if transformed_input.was_transformed() {
-                    Ok(Transformed::Yes(LogicalPlan::Filter {
-                        predicate,
-                        input: Arc::new(transformed_input.into_inner()),
-                    }))
-                } else {
-                    Ok(Transformed::No(LogicalPlan::Filter {
-                        predicate,
-                        input: Arc::new(transformed_input.into_inner()),
-                    }))
-                }
-            }
-            LogicalPlan::Projection { expressions, input } => {
-                let input_plan = Arc::try_unwrap(input).unwrap_or_else(|arc| (*arc).clone());
-                let transformed_input = f(input_plan)?;
-                if transformed_input.was_transformed() {
-                    Ok(Transformed::Yes(LogicalPlan::Projection {
-                        expressions,
-                        input: Arc::new(transformed_input.into_inner()),
-                    }))
-                } else {
-                    Ok(Transformed::No(LogicalPlan::Projection {
-                        expressions,
-                        input: Arc::new(transformed_input.into_inner()),
-                    }))
-                }
-            }
-            LogicalPlan::Join {
-                left,
-                right,
-                join_type,
-                on,
-            } => {
-                let left_plan = Arc::try_unwrap(left).unwrap_or_else(|arc| (*arc).clone());
-                let right_plan = Arc::try_unwrap(right).unwrap_or_else(|arc| (*arc).clone());
+            Err(Error::Internal(format!("visited {node:?} after stop")))
+        })
+        .unwrap();
+
+    assert!(result.was_transformed());
+    assert_eq!(result.tnode_recursion, TreeNodeRecursion::Stop);
+    assert_eq!(
+        result.into_inner(),
+        join(scan("x"), scan("b"), JoinType::Inner)
+    );
+}
 
-                let transformed_left = f(left_plan)?;
-                let transformed_right = f(right_plan)?;
+#[test]
+fn test_transform_down_stop_halts_descent() {
+    let result = nested_filters()
+        .transform_down(|node| match node {
+            LogicalPlan::Filter(_) => Ok(Transformed::new(
+                drop_predicate(node),
+                true,
+                TreeNodeRecursion::Stop,
+            )),
+            node => Ok(Transformed::no(node)),
+        })
+        .unwrap();
+
+    assert!(result.was_transformed());
+    assert_eq!(result.tnode_recursion, TreeNodeRecursion::Stop);
+
+    // Only the outer filter was rewritten
+    let LogicalPlan::Limit(limit) = result.into_inner() else {
+        panic!("expected a limit");
+    };
+    let LogicalPlan::Filter(outer) = limit.input.as_ref() else {
+        panic!("expected a filter");
+    };
+    assert_eq!(
+        outer.predicate,
+        Expr::Literal(ScalarValue::Boolean(Some(true)))
+    );
+    let LogicalPlan::Filter(inner) = outer.input.as_ref() else {
+        panic!("expected a filter");
+    };
+    assert_eq!(
+        inner.predicate,
+        binary(col("salary"), BinaryOperator::Gt, lit(50000))
+    );
+}
 
-                if transformed_left.was_transformed() || transformed_right.was_transformed() {
-                    Ok(Transformed::Yes(LogicalPlan::Join {
-                        left: Arc::new(transformed_left.into_inner()),
-                        right: Arc::new(transformed_right.into_inner()),
-                        join_type,
-                        on,
-                    }))
-                } else {
-                    Ok(Transformed::No(LogicalPlan::Join {
-                        left: Arc::new(transformed_left.into_inner()),
-                        right: Arc::new(transformed_right.into_inner()),
-                        join_type,
-                        on,
-                    }))
-                }
-            }
-            LogicalPlan::Limit { limit, input } => {
-                let input_plan = Arc::try_unwrap(input).unwrap_or_else(|arc| (*arc).clone());
-                let transformed_input = f(input_plan)?;
-                if transformed_input.was_transformed() {
-                    Ok(Transformed::Yes(LogicalPlan::Limit {
-                        limit,
-                        input: Arc::new(transformed_input.into_inner()),
-                    }))
-                } else {
-                    Ok(Transformed::No(LogicalPlan::Limit {
-                        limit,
-                        input: Arc::new(transformed_input.into_inner()),
-                    }))
-                }
+#[test]
+fn test_transform_down_jump_skips_children_only() {
+    let plan = join(
+        filter(lit(1), scan("a")),
+        filter(lit(2), scan("b")),
+        JoinType::Inner,
+    );
+    let result = plan
+        .transform_down(|node| match node {
+            LogicalPlan::Filter(_) => Ok(Transformed::new(
+                drop_predicate(node),
+                true,
+                TreeNodeRecursion::Jump,
+            )),
+            LogicalPlan::TableScan(_) => {
+                Err(Error::Internal("scan below a jump was visited".to_string()))
             }
-        }
-    }
+            node => Ok(Transformed::no(node)),
+        })
+        .unwrap();
+
+    // Both filters are rewritten, neither scan is visited
+    let expected = join(
+        drop_predicate(filter(lit(1), scan("a"))),
+        drop_predicate(filter(lit(2), scan("b"))),
+        JoinType::Inner,
+    );
+    assert!(result.was_transformed());
+    assert_eq!(result.tnode_recursion, TreeNodeRecursion::Continue);
+    assert_eq!(result.into_inner(), expected);
 }
 
-// Example optimization rules
-pub struct OptimizationRule;
+#[test]
+fn test_transform_tracked_reports_paths() {
+    let plan = join(scan("a"), filter(lit(1), scan("b")), JoinType::Inner);
+    let (result, paths) = plan
+        .transform_tracked(|node| match rename_scan(node, "b", "c") {
+            Some(renamed) => Ok(Transformed::yes(renamed)),
+            None => Ok(Transformed::no(node.clone())),
+        })
+        .unwrap();
+
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner(),
+        join(scan("a"), filter(lit(1), scan("c")), JoinType::Inner)
+    );
+    assert_eq!(paths, vec![vec![1, 0]]);
+}
 
-impl OptimizationRule {
-    /// Rule: Push down limits through projections
-    pub fn push_down_limit(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>, String> {
-        match plan {
-            LogicalPlan::Limit { limit, input } => {
-                match input.as_ref() {
-                    LogicalPlan::Projection {
-                        expressions,
-                        input: proj_input,
-                    } => {
-                        // Push limit below projection
-                        let new_limit = LogicalPlan::Limit {
-                            limit: *limit,
-                            input: proj_input.clone(),
-                        };
-                        let new_projection = LogicalPlan::Projection {
-                            expressions: expressions.clone(),
-                            input: Arc::new(new_limit),
-                        };
-                        Ok(Transformed::Yes(new_projection))
-                    }
-                    _ => Ok(Transformed::No(plan.clone())),
-                }
-            }
-            _ => Ok(Transformed::No(plan.clone())),
-        }
-    }
+/// Renames each scan after how deeply it is nested, and records the order
+/// nodes are entered and left in.
+#[derive(Default)]
+struct DepthRewriter {
+    depth: usize,
+    events: Vec<String>,
+    /// Nodes whose children are skipped
+    jump_at: Option<&'static str>,
+}
 
-    /// Rule: Remove redundant projections
-    pub fn remove_redundant_projection(
-        plan: &LogicalPlan,
-    ) -> Result<Transformed<LogicalPlan>, String> {
-        match plan {
-            LogicalPlan::Projection { expressions, input } => {
-                // Check if projection is just selecting all columns in order
-                if let LogicalPlan::TableScan {
-                    projected_columns, ..
-                } = input.as_ref()
-                {
-                    let expr_columns: Vec<String> = expressions
-                        .iter()
-                        .filter_map(|expr| match expr {
-                            Expression::Column(name) => Some(name.clone()),
-                            _ => None,
-                        })
-                        .collect();
+impl TreeNodeRewriter for DepthRewriter {
+    type Node = LogicalPlan;
 
-                    if expr_columns == *projected_columns {
-                        // Redundant projection - remove it
-                        return Ok(Transformed::Yes(input.as_ref().clone()));
-                    }
-                }
-                Ok(Transformed::No(plan.clone()))
-            }
-            _ => Ok(Transformed::No(plan.clone())),
+    fn f_down(&mut self, node: LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        self.depth += 1;
+        self.events
+            .push(format!("down {} {}", node.name(), self.depth));
+        if self.jump_at == Some(node.name()) {
+            return Ok(Transformed::new(node, false, TreeNodeRecursion::Jump));
         }
+        Ok(Transformed::no(node))
     }
 
-    /// Rule: Combine consecutive filters
-    pub fn combine_filters(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>, String> {
-        match plan {
-            LogicalPlan::Filter {
-                predicate: pred1,
-                input,
-            } => {
-                if let LogicalPlan::Filter {
-                    predicate: pred2,
-                    input: inner_input,
-                } = input.as_ref()
-                {
-                    // Combine two filters with AND
-                    let combined_predicate = Expression::BinaryOp {
-                        left: Box::new(pred1.clone()),
-                        op: BinaryOperator::And,
-                        right: Box::new(pred2.clone()),
-                    };
-                    let combined_filter = LogicalPlan::Filter {
-                        predicate: combined_predicate,
-                        input: inner_input.clone(),
-                    };
-                    Ok(Transformed::Yes(combined_filter))
-                } else {
-                    Ok(Transformed::No(plan.clone()))
-                }
+    fn f_up(&mut self, node: LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        self.events
+            .push(format!("up {} {}", node.name(), self.depth));
+        let depth = self.depth;
+        self.depth -= 1;
+        match node {
+            LogicalPlan::TableScan(scan) => {
+                Ok(Transformed::yes(LogicalPlan::TableScan(TableScan {
+                    table_name: format!("{}@{depth}", scan.table_name),
+                    ..scan
+                })))
             }
-            _ => Ok(Transformed::No(plan.clone())),
+            node => Ok(Transformed::no(node)),
         }
     }
 }
 
-// Example usage and demonstration
-fn main() -> Result<(), String> {
-    // Create a sample logical plan
-    let table_scan = LogicalPlan::TableScan {
-        table_name: "employees".to_string(),
-        projected_columns: vec!["id".to_string(), "name".to_string(), "salary".to_string()],
-    };
-
-    let filter1 = LogicalPlan::Filter {
-        predicate: Expression::BinaryOp {
-            left: Box::new(Expression::Column("salary".to_string())),
-            op: BinaryOperator::Gt,
-            right: Box::new(Expression::Literal(50000)),
-        },
-        input: Arc::new(table_scan),
-    };
-
-    let filter2 = LogicalPlan::Filter {
-        predicate: Expression::BinaryOp {
-            left: Box::new(Expression::Column("id".to_string())),
-            op: BinaryOperator::Lt,
-            right: Box::new(Expression::Literal(1000)),
-        },
-        input: Arc::new(filter1),
-    };
-
-    let projection = LogicalPlan::Projection {
-        expressions: vec![
-            Expression::Column("id".to_string()),
-            Expression::Column("name".to_string()),
-            Expression::Column("salary".to_string()),
-        ],
-        input: Arc::new(filter2),
-    };
+#[test]
+fn test_rewrite_tracks_depth() {
+    let plan = LogicalPlan::Limit(Limit {
+        fetch: 10,
+        input: Arc::new(join(scan("a"), filter(lit(1), scan("b")), JoinType::Inner)),
+    });
+    let mut rewriter = DepthRewriter::default();
+    let result = plan.rewrite(&mut rewriter).unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(
+        rewriter.events,
+        [
+            "down Limit 1",
+            "down Join 2",
+            "down TableScan 3",
+            "up TableScan 3",
+            "down Filter 3",
+            "down TableScan 4",
+            "up TableScan 4",
+            "up Filter 3",
+            "up Join 2",
+            "up Limit 1",
+        ]
+    );
+    let expected = LogicalPlan::Limit(Limit {
+        fetch: 10,
+        input: Arc::new(join(
+            scan("a@3"),
+            filter(lit(1), scan("b@4")),
+            JoinType::Inner,
+        )),
+    });
+    assert_eq!(result.into_inner(), expected);
+}
 
-    let limit = LogicalPlan::Limit {
-        limit: 10,
-        input: Arc::new(projection),
+#[test]
+fn test_rewrite_jump_skips_children() {
+    let mut rewriter = DepthRewriter {
+        jump_at: Some("Filter"),
+        ..Default::default()
     };
-
-    println!("Original plan:");
-    println!("{:#?}", limit);
-
-    // Apply optimization rules using TreeNode trait
-    println!("\n--- Applying Optimizations ---");
-
-    // 1. Combine consecutive filters
-    let optimized1 = limit.transform(OptimizationRule::combine_filters)?;
-    println!("\nAfter combining filters:");
-    println!("{:#?}", optimized1.into_inner());
-
-    // 2. Push down limit through projection
-    let optimized2 = optimized1
-        .into_inner()
-        .transform(OptimizationRule::push_down_limit)?;
-    println!("\nAfter pushing down limit:");
-    println!("{:#?}", optimized2.into_inner());
-
-    // 3. Remove redundant projection
-    let optimized3 = optimized2
-        .into_inner()
-        .transform(OptimizationRule::remove_redundant_projection)?;
-    println!("\nAfter removing redundant projection:");
-    println!("{:#?}", optimized3.into_inner());
-
-    // Example of applying multiple rules in sequence
-    let final_plan = limit.transform(|plan| {
-        let step1 = OptimizationRule::combine_filters(plan)?;
-        let step2 = OptimizationRule::push_down_limit(&step1.into_inner())?;
-        let step3 = OptimizationRule::remove_redundant_projection(&step2.into_inner())?;
-        Ok(step3)
-    })?;
-
-    println!("\nFinal optimized plan:");
-    println!("{:#?}", final_plan.into_inner());
-
-    Ok(())
+    let plan = nested_filters();
+    let result = plan.clone().rewrite(&mut rewriter).unwrap();
+    assert!(!result.was_transformed());
+    assert_eq!(result.into_inner(), plan);
+    assert_eq!(
+        rewriter.events,
+        ["down Limit 1", "down Filter 2", "up Filter 2", "up Limit 1"]
+    );
 }
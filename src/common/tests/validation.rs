@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use common::error::Error;
+use common::expr::{BinaryExpr, BinaryOperator, Expr};
+use common::plan::{Join, JoinType, Limit, LogicalPlan, Projection, TableScan};
+use common::schema::{DataType, Field, Schema};
+use common::testing::{col, filter, lit};
+
+fn scan(table_name: &str) -> LogicalPlan {
+    LogicalPlan::TableScan(TableScan {
+        table_name: table_name.to_string(),
+        table_schema: Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("salary", DataType::Int64, true),
+        ]),
+        projected_columns: vec!["id".to_string(), "salary".to_string()],
+    })
+}
+
+/// Limit -> Join(scan a, Filter(predicate) -> scan b)
+fn plan(predicate: Expr) -> LogicalPlan {
+    LogicalPlan::Limit(Limit {
+        fetch: 10,
+        input: Arc::new(LogicalPlan::Join(Join {
+            left: Arc::new(scan("a")),
+            right: Arc::new(filter(predicate, scan("b"))),
+            on: vec![("id".to_string(), "id".to_string())],
+            join_type: JoinType::Inner,
+        })),
+    })
+}
+
+fn narrow_scan(table_name: &str, column: &str) -> LogicalPlan {
+    LogicalPlan::TableScan(TableScan {
+        table_name: table_name.to_string(),
+        table_schema: Schema::new(vec![Field::new(column, DataType::Int64, false)]),
+        projected_columns: vec![column.to_string()],
+    })
+}
+
+fn join_on(left_key: &str, right_key: &str) -> LogicalPlan {
+    LogicalPlan::Join(Join {
+        left: Arc::new(narrow_scan("employees", "id")),
+        right: Arc::new(narrow_scan("salaries", "employee_id")),
+        on: vec![(left_key.to_string(), right_key.to_string())],
+        join_type: JoinType::Inner,
+    })
+}
+
+#[test]
+fn test_valid_plan() {
+    let predicate = Expr::BinaryExpr(BinaryExpr {
+        left: Box::new(col("salary")),
+        op: BinaryOperator::Gt,
+        right: Box::new(lit(50000)),
+    });
+    plan(predicate).validate().unwrap();
+}
+
+#[test]
+fn test_invalid_filter_reports_node_path() {
+    let error = plan(col("salary")).validate().unwrap_err();
+    assert_eq!(error.path(), Some([0, 1].as_slice()));
+    assert!(matches!(error.inner(), Error::Plan(_)));
+    assert_eq!(
+        error.to_string(),
+        "Error during planning: filter predicate salary must be Boolean, not Int64 at Filter (root/0/1)"
+    );
+    assert_eq!(
+        error.inner().to_string(),
+        "Error during planning: filter predicate salary must be Boolean, not Int64"
+    );
+}
+
+#[test]
+fn test_unresolved_column_reports_node_path() {
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![col("bonus")],
+        input: Arc::new(scan("a")),
+    });
+    let error = plan.validate().unwrap_err();
+    assert_eq!(error.path(), Some([].as_slice()));
+    assert!(matches!(error.inner(), Error::SchemaError(_)));
+    assert_eq!(
+        error.to_string(),
+        "Schema error: no field named bonus at Projection (root)"
+    );
+}
+
+#[test]
+fn test_join_keys_on_correct_sides() {
+    join_on("id", "employee_id").validate().unwrap();
+
+    let error = join_on("employee_id", "id").validate().unwrap_err();
+    assert_eq!(error.path(), Some([].as_slice()));
+    assert!(matches!(error.inner(), Error::Plan(_)));
+    assert!(error.inner().to_string().contains("swapped"), "{error}");
+
+    let error = join_on("id", "salary").validate().unwrap_err();
+    assert!(matches!(error.inner(), Error::Plan(_)));
+    assert!(!error.inner().to_string().contains("swapped"), "{error}");
+}
+
+#[test]
+fn test_assert_invariants() {
+    plan(Expr::IsNotNull(Box::new(col("salary"))))
+        .assert_invariants()
+        .unwrap();
+
+    let error = plan(col("salary")).assert_invariants().unwrap_err();
+    assert!(matches!(error.inner(), Error::Internal(_)));
+    assert_eq!(error.path(), Some([0, 1].as_slice()));
+    assert!(
+        error.inner().to_string().contains("must be Boolean"),
+        "{error}"
+    );
+
+    // Built directly, bypassing the duplicate check in Projection::try_new
+    let duplicated = LogicalPlan::Projection(Projection {
+        expr: vec![col("id"), col("id")],
+        input: Arc::new(scan("a")),
+    });
+    let error = duplicated.assert_invariants().unwrap_err();
+    assert!(matches!(error.inner(), Error::Internal(_)));
+    assert!(
+        error.inner().to_string().contains("more than once"),
+        "{error}"
+    );
+
+    let LogicalPlan::TableScan(table_scan) = scan("a") else {
+        unreachable!()
+    };
+    let duplicated = LogicalPlan::TableScan(TableScan {
+        projected_columns: vec!["id".to_string(), "id".to_string()],
+        ..table_scan
+    });
+    let error = duplicated.assert_invariants().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Internal error: invariant violated: Error during planning: scan projects id more than once at TableScan (root)"
+    );
+    assert_eq!(
+        error.inner().to_string(),
+        "Internal error: invariant violated: Error during planning: scan projects id more \
+         than once"
+    );
+}
@@ -1,3 +1,7 @@
+pub mod optimizer;
+pub mod rules;
+pub mod simplify_expressions;
+
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
 }
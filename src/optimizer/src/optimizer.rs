@@ -0,0 +1,302 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use common::error::{Error, Result};
+use common::plan::{Aggregate, Filter, Join, Limit, LogicalPlan, Projection, Sort};
+use common::schema::Schema;
+use common::tree_node::Transformed;
+
+use crate::rules::OptimizationRule;
+
+/// A rewrite applied to every node of a plan.
+pub type Rule = fn(&LogicalPlan) -> Result<Transformed<LogicalPlan>>;
+
+/// Runs a list of rules over a plan until none of them changes it, or until
+/// `max_passes` passes over the list.
+pub struct Optimizer {
+    /// The rules, with their names, in the order they are applied
+    pub rules: Vec<(&'static str, Rule)>,
+    pub max_passes: usize,
+    /// How long the rules may take in total before optimization stops early
+    /// with the plan so far
+    pub max_duration: Option<Duration>,
+}
+
+/// How much work a rule did during an optimization.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleStat {
+    /// Number of nodes the rule was applied to
+    pub invocations: usize,
+    /// Number of nodes the rule rewrote
+    pub fires: usize,
+    pub total_time: Duration,
+}
+
+/// Per-rule statistics of an optimization, keyed by rule name, and any
+/// warnings about how it went.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleStats {
+    pub per_rule: HashMap<String, RuleStat>,
+    pub warnings: Vec<String>,
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Optimizer {
+    /// An optimizer running the default rules.
+    pub fn new() -> Self {
+        Self::with_rules(vec![
+            (
+                "simplify_expressions",
+                OptimizationRule::simplify_expressions,
+            ),
+            (
+                "remove_redundant_cast",
+                OptimizationRule::remove_redundant_cast,
+            ),
+            ("combine_filters", OptimizationRule::combine_filters),
+            (
+                "pull_equi_filter_into_join",
+                OptimizationRule::pull_equi_filter_into_join,
+            ),
+            (
+                "propagate_join_predicates",
+                OptimizationRule::propagate_join_predicates,
+            ),
+            ("merge_projections", OptimizationRule::merge_projections),
+            (
+                "dedup_adjacent_limits_across_projection",
+                OptimizationRule::dedup_adjacent_limits_across_projection,
+            ),
+            ("push_down_limit", OptimizationRule::push_down_limit),
+            (
+                "push_projection_to_join_side",
+                OptimizationRule::push_projection_to_join_side,
+            ),
+            (
+                "remove_redundant_projection",
+                OptimizationRule::remove_redundant_projection,
+            ),
+        ])
+    }
+
+    pub fn with_rules(rules: Vec<(&'static str, Rule)>) -> Self {
+        Self {
+            rules,
+            max_passes: 3,
+            max_duration: None,
+        }
+    }
+
+    pub fn with_max_passes(mut self, max_passes: usize) -> Self {
+        self.max_passes = max_passes;
+        self
+    }
+
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Apply the rules to every node of `plan` (bottom-up), repeating until
+    /// the plan stops changing. The result is then normalized so that it
+    /// renders the same however the rules got there.
+    pub fn optimize(&self, plan: &LogicalPlan) -> Result<LogicalPlan> {
+        Ok(self.optimize_with_stats(plan)?.0)
+    }
+
+    /// Like [`Optimizer::optimize`], also reporting how often each rule ran
+    /// and rewrote a node, and how long it took. Stopping early because of
+    /// `max_duration` is reported as a warning.
+    pub fn optimize_with_stats(&self, plan: &LogicalPlan) -> Result<(LogicalPlan, RuleStats)> {
+        self.run(plan, None)
+    }
+
+    /// Like [`Optimizer::optimize`], also returning the plan as rendered by
+    /// [`LogicalPlan::display_with_schema`] after each rule that changed it,
+    /// with the rule's name. Rendering every step is costly, so only this
+    /// method does it.
+    pub fn optimize_explained(
+        &self,
+        plan: &LogicalPlan,
+    ) -> Result<(LogicalPlan, Vec<(String, String)>)> {
+        let mut snapshots = vec![];
+        let (plan, _) = self.run(plan, Some(&mut snapshots))?;
+        Ok((plan, snapshots))
+    }
+
+    fn run(
+        &self,
+        plan: &LogicalPlan,
+        mut snapshots: Option<&mut Vec<(String, String)>>,
+    ) -> Result<(LogicalPlan, RuleStats)> {
+        let mut stats = RuleStats::default();
+        let mut plan = plan.clone();
+        let mut elapsed = Duration::ZERO;
+        'passes: for _ in 0..self.max_passes {
+            let mut transformed = false;
+            for (name, rule) in &self.rules {
+                if let Some(max_duration) = self.max_duration
+                    && elapsed > max_duration
+                {
+                    stats.warnings.push(format!(
+                        "optimization stopped before {name} after {elapsed:?}, \
+                         exceeding the limit of {max_duration:?}"
+                    ));
+                    break 'passes;
+                }
+                // Checked in debug builds only, as computing schemas isn't free
+                let required = cfg!(debug_assertions).then(|| plan.schema().ok()).flatten();
+                // Only a rule that breaks a consistent plan is at fault
+                let consistent = cfg!(debug_assertions) && plan.assert_invariants().is_ok();
+                let (invocations, fires) = (Cell::new(0), Cell::new(0));
+                let start = Instant::now();
+                let result = transform_at_paths(plan, |node| {
+                    let result = rule(node)?;
+                    invocations.set(invocations.get() + 1);
+                    fires.set(fires.get() + result.transformed as usize);
+                    Ok(result)
+                })?;
+                let stat = stats.per_rule.entry(name.to_string()).or_default();
+                stat.invocations += invocations.get();
+                stat.fires += fires.get();
+                let rule_time = start.elapsed();
+                stat.total_time += rule_time;
+                elapsed += rule_time;
+                if let Some(required) = required {
+                    check_required_columns(name, &required, &result.data)?;
+                }
+                if consistent && let Err(error) = result.data.assert_invariants() {
+                    return Err(left_inconsistent(name, error));
+                }
+                if let Some(snapshots) = snapshots.as_deref_mut()
+                    && result.transformed
+                {
+                    snapshots.push((name.to_string(), result.data.display_with_schema()));
+                }
+                transformed |= result.transformed;
+                plan = result.data;
+            }
+            if !transformed {
+                break;
+            }
+        }
+        let result = transform_at_paths(plan, OptimizationRule::sort_conjuncts)?;
+        if let Some(snapshots) = snapshots
+            && result.transformed
+        {
+            snapshots.push((
+                "sort_conjuncts".to_string(),
+                result.data.display_with_schema(),
+            ));
+        }
+        Ok((result.data, stats))
+    }
+
+    /// Optimize only the subtree of `plan` at `path` (the child indices from
+    /// the root, as in validation errors) and splice the result back in. The
+    /// rest of the plan is left as it is. Fails if optimizing changed the
+    /// subtree's schema, as its parent may depend on it. Errors report paths
+    /// from the root of `plan`.
+    pub fn reoptimize_subtree(&self, plan: &LogicalPlan, path: &[usize]) -> Result<LogicalPlan> {
+        splice(plan, path, &|subtree| {
+            let optimized = self.optimize(subtree).map_err(|error| below(error, path))?;
+            let (schema, optimized_schema) = (subtree.schema()?, optimized.schema()?);
+            if optimized_schema != schema {
+                return Err(Error::Plan(format!(
+                    "optimizing {} changed its schema from {schema:?} to {optimized_schema:?}",
+                    subtree.name()
+                ))
+                .at_node(subtree.name(), path.to_vec()));
+            }
+            Ok(optimized)
+        })
+    }
+}
+
+/// Apply `f` to every node of `plan` like [`TreeNode::transform`], reporting
+/// errors at the node they were raised at.
+fn transform_at_paths<F>(plan: LogicalPlan, f: F) -> Result<Transformed<LogicalPlan>>
+where
+    F: Fn(&LogicalPlan) -> Result<Transformed<LogicalPlan>>,
+{
+    plan.transform_with_paths(|node, path| {
+        f(node).map_err(|error| error.at_node(node.name(), path.to_vec()))
+    })
+}
+
+/// The error for a rule that turned a consistent plan into an inconsistent
+/// one, at the node `assert_invariants` reported.
+fn left_inconsistent(rule: &str, error: Error) -> Error {
+    let Error::AtNode { node, path, error } = error else {
+        return Error::Internal(format!("rule {rule} left the plan inconsistent: {error}"));
+    };
+    let reason = match *error {
+        Error::Internal(reason) => reason,
+        error => error.to_string(),
+    };
+    Error::Internal(format!("rule {rule} left the plan inconsistent: {reason}")).at_node(node, path)
+}
+
+/// `error` with its node path, if any, made relative to the root of a plan
+/// that has the plan it was raised in at `prefix`.
+fn below(error: Error, prefix: &[usize]) -> Error {
+    match error {
+        Error::AtNode { node, path, error } => Error::AtNode {
+            node,
+            path: prefix.iter().chain(&path).copied().collect(),
+            error,
+        },
+        error => error,
+    }
+}
+
+/// Check that `plan` still produces every column of `required`, the schema
+/// it had before `rule` ran. Errors are reported at the root.
+fn check_required_columns(rule: &str, required: &Schema, plan: &LogicalPlan) -> Result<()> {
+    let at_root = |error: Error| error.at_node(plan.name(), vec![]);
+    let schema = plan.schema().map_err(at_root)?;
+    for field in &required.fields {
+        let column = field.column();
+        if !schema.fields.iter().any(|field| field.column() == column) {
+            return Err(at_root(Error::Internal(format!(
+                "rule {rule} dropped the required column {}",
+                field.name
+            ))));
+        }
+    }
+    Ok(())
+}
+
+/// Replace the subtree of `plan` at `path` with `f` applied to it.
+fn splice<F>(plan: &LogicalPlan, path: &[usize], f: &F) -> Result<LogicalPlan>
+where
+    F: Fn(&LogicalPlan) -> Result<LogicalPlan>,
+{
+    let Some((&index, path)) = path.split_first() else {
+        return f(plan);
+    };
+    let name = plan.name();
+    let mut plan = plan.clone();
+    let input = match (&mut plan, index) {
+        (
+            LogicalPlan::Projection(Projection { input, .. })
+            | LogicalPlan::Filter(Filter { input, .. })
+            | LogicalPlan::Limit(Limit { input, .. })
+            | LogicalPlan::Aggregate(Aggregate { input, .. })
+            | LogicalPlan::Sort(Sort { input, .. }),
+            0,
+        ) => input,
+        (LogicalPlan::Join(Join { left, .. }), 0) => left,
+        (LogicalPlan::Join(Join { right, .. }), 1) => right,
+        _ => return Err(Error::Plan(format!("{name} has no input {index}"))),
+    };
+    *input = Arc::new(splice(input, path, f)?);
+    Ok(plan)
+}
@@ -0,0 +1,658 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use common::column::Column;
+use common::error::{Error, Result};
+use common::expr::{Alias, BinaryExpr, BinaryOperator, Expr};
+use common::plan::{Filter, Join, JoinType, Limit, LogicalPlan, Projection, TableScan};
+use common::schema::Schema;
+use common::tree_node::{Transformed, TreeNode};
+
+use crate::simplify_expressions::{remove_redundant_casts, simplify};
+
+pub struct OptimizationRule;
+
+impl OptimizationRule {
+    /// Rule: Push down limits through projections
+    pub fn push_down_limit(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        match plan {
+            LogicalPlan::Limit(Limit { fetch, input }) => match input.as_ref() {
+                LogicalPlan::Projection(Projection {
+                    expr,
+                    input: proj_input,
+                }) => {
+                    // Push limit below projection
+                    let new_limit = LogicalPlan::Limit(Limit {
+                        fetch: *fetch,
+                        input: proj_input.clone(),
+                    });
+                    let new_projection = LogicalPlan::Projection(Projection {
+                        expr: expr.clone(),
+                        input: Arc::new(new_limit),
+                    });
+                    Ok(Transformed::yes(new_projection))
+                }
+                _ => Ok(Transformed::no(plan.clone())),
+            },
+            _ => Ok(Transformed::no(plan.clone())),
+        }
+    }
+
+    /// Rule: Collapse `Limit n <- Projection <- Limit m` into a projection
+    /// over a single limit of the smaller fetch
+    pub fn dedup_adjacent_limits_across_projection(
+        plan: &LogicalPlan,
+    ) -> Result<Transformed<LogicalPlan>> {
+        let LogicalPlan::Limit(Limit { fetch, input }) = plan else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        let LogicalPlan::Projection(Projection {
+            expr,
+            input: proj_input,
+        }) = input.as_ref()
+        else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        let LogicalPlan::Limit(Limit {
+            fetch: inner_fetch,
+            input: inner_input,
+        }) = proj_input.as_ref()
+        else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        let new_limit = LogicalPlan::Limit(Limit {
+            fetch: (*fetch).min(*inner_fetch),
+            input: inner_input.clone(),
+        });
+        Ok(Transformed::yes(LogicalPlan::Projection(Projection {
+            expr: expr.clone(),
+            input: Arc::new(new_limit),
+        })))
+    }
+
+    /// Rule: Remove redundant projections
+    pub fn remove_redundant_projection(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        match plan {
+            LogicalPlan::Projection(Projection { expr, input }) => {
+                // Check if projection is just selecting all columns in order
+                if let LogicalPlan::TableScan(TableScan {
+                    projected_columns, ..
+                }) = input.as_ref()
+                {
+                    // Anything but a bare column computes or renames a column
+                    let expr_columns: Option<Vec<String>> = expr
+                        .iter()
+                        .map(|expr| match expr {
+                            Expr::Column(column) => Some(column.name.clone()),
+                            _ => None,
+                        })
+                        .collect();
+
+                    if expr_columns.is_some_and(|columns| columns == *projected_columns) {
+                        // Redundant projection - remove it
+                        return Ok(Transformed::yes(input.as_ref().clone()));
+                    }
+                }
+                Ok(Transformed::no(plan.clone()))
+            }
+            _ => Ok(Transformed::no(plan.clone())),
+        }
+    }
+
+    /// Rule: Combine consecutive filters
+    pub fn combine_filters(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        match plan {
+            LogicalPlan::Filter(Filter {
+                predicate: pred1,
+                input,
+            }) => {
+                if let LogicalPlan::Filter(Filter {
+                    predicate: pred2,
+                    input: inner_input,
+                }) = input.as_ref()
+                {
+                    // Combine two filters with AND
+                    let combined_predicate = Expr::BinaryExpr(BinaryExpr {
+                        left: Box::new(pred1.clone()),
+                        op: BinaryOperator::And,
+                        right: Box::new(pred2.clone()),
+                    });
+                    let combined_filter = LogicalPlan::Filter(Filter {
+                        predicate: combined_predicate,
+                        input: inner_input.clone(),
+                    });
+                    Ok(Transformed::yes(combined_filter))
+                } else {
+                    Ok(Transformed::no(plan.clone()))
+                }
+            }
+            _ => Ok(Transformed::no(plan.clone())),
+        }
+    }
+
+    /// Rule: Simplify the expressions owned by a node
+    pub fn simplify_expressions(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        plan.rewrite_expressions(simplify)
+    }
+
+    /// Rule: Remove casts that can't change the value they are applied to
+    pub fn remove_redundant_cast(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        plan.rewrite_expressions(remove_redundant_casts)
+    }
+
+    /// Rule: Merge a projection into the projection below it by substituting
+    /// the lower projection's expressions, seeing through their aliases. A
+    /// computed expression referenced more than once is not duplicated: the
+    /// projections are then left separate.
+    pub fn merge_projections(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        let LogicalPlan::Projection(Projection { expr, input }) = plan else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        let LogicalPlan::Projection(Projection {
+            expr: lower_expr,
+            input: lower_input,
+        }) = input.as_ref()
+        else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        let lower_schema = input.schema()?;
+        let inner_schema = lower_input.schema()?;
+
+        let references = RefCell::new(vec![0; lower_expr.len()]);
+        let merged = expr
+            .iter()
+            .map(|expr| {
+                let substituted = expr.transform(|expr| match expr {
+                    Expr::Column(column) => {
+                        let index = lower_schema.index_of_column(column)?;
+                        references.borrow_mut()[index] += 1;
+                        let lower = match &lower_expr[index] {
+                            Expr::Alias(Alias { expr, .. }) => expr.as_ref(),
+                            expr => expr,
+                        };
+                        Ok(Transformed::yes(lower.clone()))
+                    }
+                    _ => Ok(Transformed::no(expr.clone())),
+                })?;
+                // Keep the projection's output names unchanged
+                let name = expr.to_field(&lower_schema)?.name;
+                if substituted.data.to_field(&inner_schema)?.name == name {
+                    Ok(substituted.data)
+                } else {
+                    Ok(Expr::Alias(Alias {
+                        expr: Box::new(strip_alias(substituted.data)),
+                        name,
+                    }))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let duplicated = references
+            .into_inner()
+            .iter()
+            .zip(lower_expr)
+            .any(|(references, expr)| *references > 1 && is_computed(expr));
+        if duplicated {
+            return Ok(Transformed::no(plan.clone()));
+        }
+        Ok(Transformed::yes(LogicalPlan::Projection(Projection {
+            expr: merged,
+            input: lower_input.clone(),
+        })))
+    }
+
+    /// Rule: Move `left_col = right_col` conditions of a filter over an inner
+    /// join into the join's keys. Outer joins are left alone, as filtering
+    /// after them also removes the NULL-padded rows.
+    pub fn pull_equi_filter_into_join(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        let LogicalPlan::Filter(Filter { predicate, input }) = plan else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        let LogicalPlan::Join(join) = input.as_ref() else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        if join.join_type != JoinType::Inner {
+            return Ok(Transformed::no(plan.clone()));
+        }
+        let left_schema = join.left.schema()?;
+        let right_schema = join.right.schema()?;
+
+        let mut on = join.on.clone();
+        let mut remaining = vec![];
+        for conjunct in split_conjunction(predicate) {
+            if let Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: BinaryOperator::Eq,
+                right,
+            }) = conjunct
+                && let (Expr::Column(left), Expr::Column(right)) = (left.as_ref(), right.as_ref())
+            {
+                let side = |column: &Column| {
+                    join_side(&Expr::Column(column.clone()), &left_schema, &right_schema)
+                };
+                match (side(left), side(right)) {
+                    (Some(0), Some(1)) => {
+                        on.push((left.name.clone(), right.name.clone()));
+                        continue;
+                    }
+                    (Some(1), Some(0)) => {
+                        on.push((right.name.clone(), left.name.clone()));
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            remaining.push(conjunct.clone());
+        }
+        if on.len() == join.on.len() {
+            return Ok(Transformed::no(plan.clone()));
+        }
+
+        let join = LogicalPlan::Join(Join { on, ..join.clone() });
+        Ok(Transformed::yes(match conjunction(remaining) {
+            Some(predicate) => LogicalPlan::Filter(Filter {
+                predicate,
+                input: Arc::new(join),
+            }),
+            None => join,
+        }))
+    }
+
+    /// Rule: Restate the conditions an inner join's input is known to meet
+    /// on a join key for the matching key of the other input, and filter
+    /// that input by them. The conditions are those of a filter over the
+    /// join, e.g. `a.id > 5` over a join on `a.id = b.id` filters the right
+    /// input by `b.id > 5`, and those of a filter that is an input of the
+    /// join. The filters the conditions come from are kept, and a condition
+    /// the other input's filter already has is not added again, so
+    /// propagation ends
+    pub fn propagate_join_predicates(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        match plan {
+            LogicalPlan::Filter(Filter { predicate, input }) => {
+                let LogicalPlan::Join(join) = input.as_ref() else {
+                    return Ok(Transformed::no(plan.clone()));
+                };
+                let schemas = [join.left.schema()?, join.right.schema()?];
+                let conjuncts = split_conjunction(predicate);
+                let on_side = |side| {
+                    conjuncts
+                        .iter()
+                        .copied()
+                        .filter(|conjunct| {
+                            join_side(conjunct, &schemas[0], &schemas[1]) == Some(side)
+                        })
+                        .collect()
+                };
+                let Some(join) = propagate_across_join(join, [on_side(0), on_side(1)])? else {
+                    return Ok(Transformed::no(plan.clone()));
+                };
+                Ok(Transformed::yes(LogicalPlan::Filter(Filter {
+                    predicate: predicate.clone(),
+                    input: Arc::new(LogicalPlan::Join(join)),
+                })))
+            }
+            LogicalPlan::Join(join) => {
+                let conjuncts = [&join.left, &join.right].map(|input| match input.as_ref() {
+                    LogicalPlan::Filter(Filter { predicate, .. }) => split_conjunction(predicate),
+                    _ => vec![],
+                });
+                match propagate_across_join(join, conjuncts)? {
+                    Some(join) => Ok(Transformed::yes(LogicalPlan::Join(join))),
+                    None => Ok(Transformed::no(plan.clone())),
+                }
+            }
+            _ => Ok(Transformed::no(plan.clone())),
+        }
+    }
+
+    /// Rule: Make a plan produce its columns in the `required` order, e.g. the
+    /// column order of an insert target. A projection at the root is
+    /// reordered; any other plan gets a projection of its columns. Fails
+    /// unless `required` names each output column exactly once
+    pub fn align_projection_order(
+        plan: &LogicalPlan,
+        required: &[&str],
+    ) -> Result<Transformed<LogicalPlan>> {
+        let names = plan.output_names()?;
+        if names == required {
+            return Ok(Transformed::no(plan.clone()));
+        }
+        let mut sorted_names: Vec<_> = names.iter().map(String::as_str).collect();
+        let mut sorted_required = required.to_vec();
+        sorted_names.sort_unstable();
+        sorted_required.sort_unstable();
+        if sorted_names != sorted_required || sorted_names.windows(2).any(|w| w[0] == w[1]) {
+            return Err(Error::Plan(format!(
+                "can't order columns {names:?} as {required:?}"
+            )));
+        }
+        let position = |name: &str| names.iter().position(|other| other == name);
+        let (expr, input) = match plan {
+            LogicalPlan::Projection(Projection { expr, input }) => (expr.clone(), input.clone()),
+            _ => {
+                let schema = plan.schema()?;
+                let columns = schema
+                    .fields
+                    .iter()
+                    .map(|field| Expr::Column(field.column()));
+                (columns.collect(), Arc::new(plan.clone()))
+            }
+        };
+        let expr = required
+            .iter()
+            .filter_map(|name| position(name).map(|index| expr[index].clone()))
+            .collect();
+        Ok(Transformed::yes(LogicalPlan::Projection(Projection {
+            expr,
+            input,
+        })))
+    }
+
+    /// Rule: Sort the conjuncts of filter predicates by their display form,
+    /// so that the same conditions always render in the same order whatever
+    /// order the other rules produced them in. AND stops at the first FALSE
+    /// conjunct, so a conjunct that may fail (see [`Expr::may_fail`]) could
+    /// be guarded by the ones before it: it is never moved, and only the
+    /// conjuncts between two such conjuncts are sorted.
+    pub fn sort_conjuncts(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        let LogicalPlan::Filter(Filter { predicate, input }) = plan else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        let conjuncts = split_conjunction(predicate);
+        let mut sorted: Vec<(String, &Expr)> = conjuncts
+            .iter()
+            .map(|conjunct| (conjunct.to_string(), *conjunct))
+            .collect();
+        for run in sorted.split_mut(|(_, conjunct)| conjunct.may_fail()) {
+            run.sort_by(|(left, _), (right, _)| left.cmp(right));
+        }
+        if sorted
+            .iter()
+            .zip(&conjuncts)
+            .all(|((_, sorted), conjunct)| std::ptr::eq(*sorted, *conjunct))
+        {
+            return Ok(Transformed::no(plan.clone()));
+        }
+        let predicate = conjunction(sorted.into_iter().map(|(_, expr)| expr.clone()).collect())
+            .expect("a predicate has at least one conjunct");
+        Ok(Transformed::yes(LogicalPlan::Filter(Filter {
+            predicate,
+            input: input.clone(),
+        })))
+    }
+
+    /// Rule: Compute projected expressions that only use one side of a join
+    /// in a projection on that side, so they are evaluated before the join
+    /// multiplies the rows. Only sides the join keeps every row of are used:
+    /// on a side an outer join pads with NULLs, the expression would see the
+    /// padding instead of its own result
+    pub fn push_projection_to_join_side(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+        let LogicalPlan::Projection(Projection { expr, input }) = plan else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        let LogicalPlan::Join(join) = input.as_ref() else {
+            return Ok(Transformed::no(plan.clone()));
+        };
+        let preserved = match join.join_type {
+            JoinType::Inner => [true, true],
+            JoinType::Left => [true, false],
+            JoinType::Right => [false, true],
+            JoinType::Full => [false, false],
+        };
+        let join_schema = input.schema()?;
+        let left_schema = join.left.schema()?;
+        let right_schema = join.right.schema()?;
+
+        let mut pushed: [Vec<Expr>; 2] = [vec![], vec![]];
+        let mut outer_expr = Vec::with_capacity(expr.len());
+        for expr in expr {
+            let side = match expr {
+                // Plain column references have nothing to compute
+                Expr::Column(_) => None,
+                Expr::Alias(Alias { expr, .. }) if matches!(expr.as_ref(), Expr::Column(_)) => None,
+                expr => join_side(expr, &left_schema, &right_schema),
+            };
+            let name = expr.to_field(&join_schema)?.name;
+            // The pushed expression is referenced by name above the join
+            let name_taken = [&left_schema, &right_schema]
+                .iter()
+                .any(|schema| schema.fields.iter().any(|field| field.name == name));
+            let side = side.filter(|&side| preserved[side] && !name_taken);
+            match side {
+                Some(side) => {
+                    pushed[side].push(match expr {
+                        Expr::Alias(_) => expr.clone(),
+                        _ => Expr::Alias(Alias {
+                            expr: Box::new(expr.clone()),
+                            name: name.clone(),
+                        }),
+                    });
+                    outer_expr.push(Expr::Column(Column::new_unqualified(name)));
+                }
+                None => outer_expr.push(expr.clone()),
+            }
+        }
+        if pushed.iter().all(Vec::is_empty) {
+            return Ok(Transformed::no(plan.clone()));
+        }
+
+        let [left_pushed, right_pushed] = pushed;
+        let extend = |side: &Arc<LogicalPlan>, schema: &Schema, pushed: Vec<Expr>| {
+            if pushed.is_empty() {
+                return side.clone();
+            }
+            let mut expr: Vec<Expr> = schema
+                .fields
+                .iter()
+                .map(|field| Expr::Column(field.column()))
+                .collect();
+            expr.extend(pushed);
+            Arc::new(LogicalPlan::Projection(Projection {
+                expr,
+                input: side.clone(),
+            }))
+        };
+        let join = LogicalPlan::Join(Join {
+            left: extend(&join.left, &left_schema, left_pushed),
+            right: extend(&join.right, &right_schema, right_pushed),
+            ..join.clone()
+        });
+        Ok(Transformed::yes(LogicalPlan::Projection(Projection {
+            expr: outer_expr,
+            input: Arc::new(join),
+        })))
+    }
+}
+
+fn strip_alias(expr: Expr) -> Expr {
+    match expr {
+        Expr::Alias(Alias { expr, .. }) => *expr,
+        expr => expr,
+    }
+}
+
+/// Whether evaluating `expr` does more than read a column or a constant.
+fn is_computed(expr: &Expr) -> bool {
+    match expr {
+        Expr::Alias(Alias { expr, .. }) => is_computed(expr),
+        Expr::Column(_) | Expr::Literal(_) => false,
+        _ => true,
+    }
+}
+
+/// Split `a AND b AND ...` into its conjuncts, in order.
+fn split_conjunction(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: BinaryOperator::And,
+            right,
+        }) => {
+            let mut conjuncts = split_conjunction(left);
+            conjuncts.extend(split_conjunction(right));
+            conjuncts
+        }
+        expr => vec![expr],
+    }
+}
+
+/// Join `conjuncts` with AND, left-deep.
+fn conjunction(conjuncts: Vec<Expr>) -> Option<Expr> {
+    conjuncts.into_iter().reduce(|left, right| {
+        Expr::BinaryExpr(BinaryExpr {
+            left: Box::new(left),
+            op: BinaryOperator::And,
+            right: Box::new(right),
+        })
+    })
+}
+
+/// The join input (0 for left, 1 for right) that all the columns of `expr`
+/// come from, if there is exactly one.
+fn join_side(expr: &Expr, left_schema: &Schema, right_schema: &Schema) -> Option<usize> {
+    let columns = expr.column_refs();
+    if columns.is_empty() {
+        return None;
+    }
+    let resolves_in = |schema: &Schema| {
+        columns
+            .iter()
+            .all(|column| schema.index_of_column(column).is_ok())
+    };
+    match (resolves_in(left_schema), resolves_in(right_schema)) {
+        (true, false) => Some(0),
+        (false, true) => Some(1),
+        _ => None,
+    }
+}
+
+/// `join` with each input filtered by the conditions derived from the
+/// `conjuncts` known to hold for the other input, or `None` if there are
+/// no new ones. Only inner joins are rewritten: an outer join keeps the
+/// unmatched rows of an input, which the derived conditions would remove.
+fn propagate_across_join(join: &Join, conjuncts: [Vec<&Expr>; 2]) -> Result<Option<Join>> {
+    if join.join_type != JoinType::Inner {
+        return Ok(None);
+    }
+    let schemas = [join.left.schema()?, join.right.schema()?];
+    let mut new_inputs = [join.left.clone(), join.right.clone()];
+    let mut transformed = false;
+    for from in 0..2 {
+        let to = 1 - from;
+        let keys: Vec<_> = join
+            .on
+            .iter()
+            .map(|(left, right)| {
+                if from == 0 {
+                    (left, right)
+                } else {
+                    (right, left)
+                }
+            })
+            .collect();
+        let derived = derive_key_predicates(&conjuncts[from], &keys, &schemas[from], &schemas[to])?;
+        if let Some(input) = add_conjuncts(&new_inputs[to], derived)? {
+            new_inputs[to] = Arc::new(input);
+            transformed = true;
+        }
+    }
+    if !transformed {
+        return Ok(None);
+    }
+    let [left, right] = new_inputs;
+    Ok(Some(Join {
+        left,
+        right,
+        ..join.clone()
+    }))
+}
+
+/// The conjuncts that constrain nothing but a join key of one input (with
+/// `from` as its schema), restated for the matching key of the other input.
+/// `keys` pairs each key of the first input with the matching key of the
+/// second. Conjuncts that may fail are skipped: on the other input they
+/// would also be evaluated for rows that never match.
+fn derive_key_predicates(
+    conjuncts: &[&Expr],
+    keys: &[(&String, &String)],
+    from: &Schema,
+    to: &Schema,
+) -> Result<Vec<Expr>> {
+    let key_index =
+        |schema: &Schema, key: &str| schema.index_of_column(&Column::new_unqualified(key)).ok();
+    let mut derived = vec![];
+    for conjunct in conjuncts {
+        if conjunct.may_fail() {
+            continue;
+        }
+        let columns = conjunct.column_refs();
+        let [column] = columns.iter().collect::<Vec<_>>()[..] else {
+            continue;
+        };
+        let Ok(index) = from.index_of_column(column) else {
+            continue;
+        };
+        for (from_key, to_key) in keys {
+            if key_index(from, from_key) != Some(index) {
+                continue;
+            }
+            let Some(to_index) = key_index(to, to_key) else {
+                continue;
+            };
+            let to_column = to.fields[to_index].column();
+            let restated = conjunct.transform(|expr| match expr {
+                Expr::Column(other) if other == column => {
+                    Ok(Transformed::yes(Expr::Column(to_column.clone())))
+                }
+                _ => Ok(Transformed::no(expr.clone())),
+            })?;
+            derived.push(restated.data);
+        }
+    }
+    Ok(derived)
+}
+
+/// `input` filtered by those of `conjuncts` that its own filter doesn't
+/// already have, or `None` if there are none. Columns are compared as
+/// resolved against the input's schema.
+fn add_conjuncts(input: &Arc<LogicalPlan>, conjuncts: Vec<Expr>) -> Result<Option<LogicalPlan>> {
+    let schema = input.schema()?;
+    let qualify = |expr: &Expr| {
+        expr.transform(|expr| match expr {
+            Expr::Column(column) => match schema.field_from_column(column) {
+                Ok(field) => Ok(Transformed::yes(Expr::Column(field.column()))),
+                Err(_) => Ok(Transformed::no(expr.clone())),
+            },
+            _ => Ok(Transformed::no(expr.clone())),
+        })
+        .map(|expr| expr.data)
+    };
+    let (existing, filter_input) = match input.as_ref() {
+        LogicalPlan::Filter(Filter { predicate, input }) => {
+            (split_conjunction(predicate), input.clone())
+        }
+        _ => (vec![], input.clone()),
+    };
+    let mut known = existing
+        .iter()
+        .map(|expr| qualify(expr))
+        .collect::<Result<Vec<_>>>()?;
+    let mut added = vec![];
+    for conjunct in conjuncts {
+        let qualified = qualify(&conjunct)?;
+        if !known.contains(&qualified) {
+            known.push(qualified);
+            added.push(conjunct);
+        }
+    }
+    if added.is_empty() {
+        return Ok(None);
+    }
+    let conjuncts = existing.into_iter().cloned().chain(added).collect();
+    Ok(conjunction(conjuncts).map(|predicate| {
+        LogicalPlan::Filter(Filter {
+            predicate,
+            input: filter_input,
+        })
+    }))
+}
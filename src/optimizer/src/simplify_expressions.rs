@@ -0,0 +1,212 @@
+use common::error::Result;
+use common::expr::{BinaryExpr, BinaryOperator, Case, Cast, Expr};
+use common::scalar::ScalarValue;
+use common::schema::{DataType, Schema};
+use common::tree_node::{Transformed, TreeNode};
+
+/// Simplify an expression evaluated against `schema` and all its
+/// sub-expressions (bottom-up).
+pub fn simplify(expr: &Expr, schema: &Schema) -> Result<Transformed<Expr>> {
+    expr.transform(|expr| simplify_node(expr, schema))
+}
+
+/// Remove CASTs that can't change their input from an expression evaluated
+/// against `schema` (bottom-up):
+/// - `CAST(x AS T)` where `x` is already of type `T` becomes `x`
+/// - `CAST(CAST(x AS A) AS B)` becomes `CAST(x AS B)` when the inner cast is
+///   lossless, i.e. `x`'s type widens to `A`. A narrowing inner cast is kept,
+///   as it may truncate or fail.
+pub fn remove_redundant_casts(expr: &Expr, schema: &Schema) -> Result<Transformed<Expr>> {
+    expr.transform(|expr| {
+        let Expr::Cast(Cast {
+            expr: inner,
+            data_type,
+        }) = expr
+        else {
+            return Ok(Transformed::no(expr.clone()));
+        };
+        if inner.get_type(schema)? == *data_type {
+            return Ok(Transformed::yes(inner.as_ref().clone()));
+        }
+        if let Expr::Cast(Cast {
+            expr: input,
+            data_type: intermediate,
+        }) = inner.as_ref()
+            && input.get_type(schema)?.can_widen_to(intermediate)
+        {
+            let cast = Expr::Cast(Cast {
+                expr: input.clone(),
+                data_type: *data_type,
+            });
+            // The outer cast may now be a no-op itself
+            return Ok(Transformed::yes(
+                remove_redundant_casts(&cast, schema)?.into_inner(),
+            ));
+        }
+        Ok(Transformed::no(expr.clone()))
+    })
+}
+
+/// Simplify a single expression node, assuming its children are already
+/// simplified.
+fn simplify_node(expr: &Expr, schema: &Schema) -> Result<Transformed<Expr>> {
+    match expr {
+        Expr::Case(case) if case.expr.is_none() => simplify_case(case, schema),
+        Expr::Coalesce(args) => simplify_coalesce(args, schema),
+        Expr::BinaryExpr(binary) if is_comparison(binary.op) => Ok(simplify_comparison(binary)),
+        Expr::BinaryExpr(binary) => simplify_arithmetic(binary, schema),
+        _ => Ok(Transformed::no(expr.clone())),
+    }
+}
+
+/// Remove CASE branches whose condition is a constant.
+///
+/// Only literal conditions are recognised, so non-trivial constant conditions
+/// must be folded to a literal beforehand. A CASE left without branches or
+/// ELSE becomes a NULL of the type the CASE had.
+fn simplify_case(case: &Case, schema: &Schema) -> Result<Transformed<Expr>> {
+    // WHEN false / WHEN NULL can never be taken
+    let when_then_expr: Vec<_> = case
+        .when_then_expr
+        .iter()
+        .filter(|(when, _)| literal_bool(when) != Some(false) && !is_null_literal(when))
+        .cloned()
+        .collect();
+
+    let simplified = match when_then_expr.first() {
+        // CASE WHEN true THEN a ... END => a
+        Some((when, then)) if literal_bool(when) == Some(true) => then.as_ref().clone(),
+        // CASE ELSE b END => b
+        None => match &case.else_expr {
+            Some(else_expr) => else_expr.as_ref().clone(),
+            None => {
+                let data_type = Expr::Case(case.clone()).get_type(schema)?;
+                Expr::Literal(ScalarValue::new_null(&data_type))
+            }
+        },
+        Some(_) if when_then_expr.len() < case.when_then_expr.len() => Expr::Case(Case {
+            expr: None,
+            when_then_expr,
+            else_expr: case.else_expr.clone(),
+        }),
+        Some(_) => return Ok(Transformed::no(Expr::Case(case.clone()))),
+    };
+    Ok(Transformed::yes(simplified))
+}
+
+/// Simplify a COALESCE by
+/// - dropping NULL literal arguments, which never produce the result
+/// - dropping the arguments after one that can't be NULL, which are never
+///   reached
+/// - replacing a COALESCE of a single argument with that argument
+///
+/// The dropped arguments may have widened the result's type, e.g.
+/// `COALESCE(int32_col, int64_col)` is an Int64, so the simplified
+/// expression is cast back to it if needed.
+fn simplify_coalesce(args: &[Expr], schema: &Schema) -> Result<Transformed<Expr>> {
+    let mut simplified: Vec<Expr> = Vec::with_capacity(args.len());
+    for arg in args {
+        if is_null_literal(arg) {
+            continue;
+        }
+        simplified.push(arg.clone());
+        if !arg.nullable(schema)? {
+            break;
+        }
+    }
+
+    let expr = match (simplified.len(), args.last()) {
+        // Every argument is NULL; keep the last
+        (0, Some(last)) => last.clone(),
+        (1, _) => simplified.remove(0),
+        (len, _) if len < args.len() => Expr::Coalesce(simplified),
+        _ => return Ok(Transformed::no(Expr::Coalesce(args.to_vec()))),
+    };
+    let data_type = Expr::Coalesce(args.to_vec()).get_type(schema)?;
+    if expr.get_type(schema)? == data_type {
+        return Ok(Transformed::yes(expr));
+    }
+    Ok(Transformed::yes(Expr::Cast(Cast {
+        expr: Box::new(expr),
+        data_type,
+    })))
+}
+
+/// A comparison with a NULL literal is never true or false, so it folds to a
+/// boolean NULL.
+fn simplify_comparison(binary: &BinaryExpr) -> Transformed<Expr> {
+    if is_null_literal(&binary.left) || is_null_literal(&binary.right) {
+        Transformed::yes(Expr::Literal(ScalarValue::Boolean(None)))
+    } else {
+        Transformed::no(Expr::BinaryExpr(binary.clone()))
+    }
+}
+
+/// Remove arithmetic identities: `x + 0`, `x - 0`, `x * 1`, `x / 1` and
+/// `x * 0`. An identity only folds to `x` when `x` already has the result's
+/// type, e.g. `int32_col + 0` is an Int64. `x * 0` folds to a zero of the
+/// result's type, and only for non-nullable `x`, since `NULL * 0` is NULL,
+/// and non-float `x`, since `inf * 0` is NaN.
+fn simplify_arithmetic(binary: &BinaryExpr, schema: &Schema) -> Result<Transformed<Expr>> {
+    let BinaryExpr { left, op, right } = binary;
+    let unchanged = || Ok(Transformed::no(Expr::BinaryExpr(binary.clone())));
+    let identity = match op {
+        BinaryOperator::Plus if is_int_literal(right, 0) => Some(left),
+        BinaryOperator::Plus if is_int_literal(left, 0) => Some(right),
+        BinaryOperator::Minus if is_int_literal(right, 0) => Some(left),
+        BinaryOperator::Multiply if is_int_literal(right, 1) => Some(left),
+        BinaryOperator::Multiply if is_int_literal(left, 1) => Some(right),
+        BinaryOperator::Divide if is_int_literal(right, 1) => Some(left),
+        _ => None,
+    };
+    let zeroed = match op {
+        BinaryOperator::Multiply if is_int_literal(right, 0) => Some(left),
+        BinaryOperator::Multiply if is_int_literal(left, 0) => Some(right),
+        _ => None,
+    };
+    if identity.is_none() && zeroed.is_none() {
+        return unchanged();
+    }
+
+    let data_type = Expr::BinaryExpr(binary.clone()).get_type(schema)?;
+    if let Some(operand) = identity {
+        if operand.get_type(schema)? != data_type {
+            return unchanged();
+        }
+        return Ok(Transformed::yes(operand.as_ref().clone()));
+    }
+    match zeroed {
+        Some(operand) if !operand.nullable(schema)? && data_type != DataType::Float64 => {
+            let zero = ScalarValue::Int64(Some(0)).cast_to(&data_type)?;
+            Ok(Transformed::yes(Expr::Literal(zero)))
+        }
+        _ => unchanged(),
+    }
+}
+
+fn is_comparison(op: BinaryOperator) -> bool {
+    matches!(
+        op,
+        BinaryOperator::Eq
+            | BinaryOperator::Ne
+            | BinaryOperator::Lt
+            | BinaryOperator::Le
+            | BinaryOperator::Gt
+            | BinaryOperator::Ge
+    )
+}
+
+fn is_int_literal(expr: &Expr, value: i64) -> bool {
+    *expr == Expr::Literal(ScalarValue::Int64(Some(value)))
+}
+
+fn literal_bool(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Literal(ScalarValue::Boolean(value)) => *value,
+        _ => None,
+    }
+}
+
+fn is_null_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Literal(value) if value.is_null())
+}
@@ -0,0 +1,554 @@
+use std::sync::Arc;
+
+use common::expr::{Alias, BinaryOperator, Expr};
+use common::plan::{Join, JoinType, Limit, LogicalPlan, Projection, TableScan};
+use common::schema::{DataType, Field, Schema};
+use common::testing::{alias, assert_plan_shape, binary, col, filter, join, lit, qualified_col};
+use common::tree_node::{Transformed, TreeNode};
+use optimizer::rules::OptimizationRule;
+
+fn employees_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("salary", DataType::Int64, true),
+    ])
+}
+
+fn scan(table_name: &str) -> LogicalPlan {
+    LogicalPlan::TableScan(TableScan {
+        table_name: table_name.to_string(),
+        table_schema: employees_schema(),
+        projected_columns: vec!["id".to_string(), "name".to_string(), "salary".to_string()],
+    })
+}
+
+fn projection(expr: Vec<Expr>, input: LogicalPlan) -> LogicalPlan {
+    LogicalPlan::Projection(Projection {
+        expr,
+        input: Arc::new(input),
+    })
+}
+
+fn employee_columns() -> Vec<Expr> {
+    vec![col("id"), col("name"), col("salary")]
+}
+
+fn limit(fetch: usize, input: LogicalPlan) -> LogicalPlan {
+    LogicalPlan::Limit(Limit {
+        fetch,
+        input: Arc::new(input),
+    })
+}
+
+fn salary_gt() -> Expr {
+    binary(col("salary"), BinaryOperator::Gt, lit(50000))
+}
+
+fn id_lt() -> Expr {
+    binary(col("id"), BinaryOperator::Lt, lit(1000))
+}
+
+/// Limit -> Projection -> Filter -> Filter -> TableScan
+fn example_plan() -> LogicalPlan {
+    limit(
+        10,
+        projection(
+            employee_columns(),
+            filter(id_lt(), filter(salary_gt(), scan("employees"))),
+        ),
+    )
+}
+
+fn combined_predicate() -> Expr {
+    binary(id_lt(), BinaryOperator::And, salary_gt())
+}
+
+#[test]
+fn test_combine_filters() {
+    let result = example_plan()
+        .transform(OptimizationRule::combine_filters)
+        .unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner(),
+        limit(
+            10,
+            projection(
+                employee_columns(),
+                filter(combined_predicate(), scan("employees"))
+            )
+        )
+    );
+}
+
+#[test]
+fn test_push_down_limit() {
+    let result = example_plan()
+        .transform(OptimizationRule::push_down_limit)
+        .unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner(),
+        projection(
+            employee_columns(),
+            limit(10, filter(id_lt(), filter(salary_gt(), scan("employees"))))
+        )
+    );
+}
+
+#[test]
+fn test_dedup_adjacent_limits_across_projection() {
+    let plan = limit(
+        10,
+        projection(employee_columns(), limit(5, scan("employees"))),
+    );
+    let result = plan
+        .transform(OptimizationRule::dedup_adjacent_limits_across_projection)
+        .unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner(),
+        projection(employee_columns(), limit(5, scan("employees")))
+    );
+
+    let plan = limit(
+        3,
+        projection(employee_columns(), limit(5, scan("employees"))),
+    );
+    let result = plan
+        .transform(OptimizationRule::dedup_adjacent_limits_across_projection)
+        .unwrap();
+    assert_eq!(
+        result.into_inner(),
+        projection(employee_columns(), limit(3, scan("employees")))
+    );
+
+    // Without an inner limit there is nothing to collapse
+    let result = example_plan()
+        .transform(OptimizationRule::dedup_adjacent_limits_across_projection)
+        .unwrap();
+    assert!(!result.was_transformed());
+}
+
+#[test]
+fn test_remove_redundant_projection() {
+    let result = projection(employee_columns(), scan("employees"))
+        .transform(OptimizationRule::remove_redundant_projection)
+        .unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(result.into_inner(), scan("employees"));
+
+    // The projection is not directly over the scan
+    let plan = example_plan();
+    let result = plan
+        .transform(OptimizationRule::remove_redundant_projection)
+        .unwrap();
+    assert!(!result.was_transformed());
+    assert_eq!(result.into_inner(), plan);
+}
+
+#[test]
+fn test_rules_in_sequence() {
+    let result = example_plan()
+        .transform(|plan| {
+            let step1 = OptimizationRule::combine_filters(plan)?;
+            let step2 = OptimizationRule::push_down_limit(&step1.data)?;
+            let step3 = OptimizationRule::remove_redundant_projection(&step2.data)?;
+            Ok(Transformed::new(
+                step3.data,
+                step1.transformed || step2.transformed || step3.transformed,
+                step3.tnode_recursion,
+            ))
+        })
+        .unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner(),
+        projection(
+            employee_columns(),
+            limit(10, filter(combined_predicate(), scan("employees")))
+        )
+    );
+}
+
+#[test]
+fn test_push_projection_to_join_side() {
+    let doubled = binary(
+        qualified_col("a", "salary"),
+        BinaryOperator::Multiply,
+        lit(2),
+    );
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![qualified_col("b", "name"), doubled.clone()],
+        input: Arc::new(join(scan("a"), scan("b"), JoinType::Inner)),
+    });
+
+    let result = OptimizationRule::push_projection_to_join_side(&plan).unwrap();
+    assert!(result.was_transformed());
+    let left = LogicalPlan::Projection(Projection {
+        expr: vec![
+            qualified_col("a", "id"),
+            qualified_col("a", "name"),
+            qualified_col("a", "salary"),
+            Expr::Alias(Alias {
+                expr: Box::new(doubled),
+                name: "a.salary * 2".to_string(),
+            }),
+        ],
+        input: Arc::new(scan("a")),
+    });
+    let expected = LogicalPlan::Projection(Projection {
+        expr: vec![qualified_col("b", "name"), col("a.salary * 2")],
+        input: Arc::new(join(left, scan("b"), JoinType::Inner)),
+    });
+    let optimized = result.into_inner();
+    assert_eq!(optimized, expected);
+    assert_eq!(
+        optimized.output_names().unwrap(),
+        plan.output_names().unwrap()
+    );
+}
+
+#[test]
+fn test_push_projection_to_join_side_keeps_mixed_expressions() {
+    // Uses both sides, so it can only be computed after the join
+    let total = binary(
+        qualified_col("a", "salary"),
+        BinaryOperator::Plus,
+        qualified_col("b", "salary"),
+    );
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![total, qualified_col("a", "id")],
+        input: Arc::new(join(scan("a"), scan("b"), JoinType::Inner)),
+    });
+
+    let result = OptimizationRule::push_projection_to_join_side(&plan).unwrap();
+    assert!(!result.was_transformed());
+    assert_eq!(result.into_inner(), plan);
+}
+
+#[test]
+fn test_push_projection_to_join_side_keeps_outer_join_padding() {
+    // Unmatched rows must see the NULL padding, giving 0
+    let salary = Expr::Coalesce(vec![qualified_col("b", "salary"), lit(0)]);
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![qualified_col("a", "id"), salary],
+        input: Arc::new(join(scan("a"), scan("b"), JoinType::Left)),
+    });
+
+    let result = OptimizationRule::push_projection_to_join_side(&plan).unwrap();
+    assert!(!result.was_transformed());
+    assert_eq!(result.into_inner(), plan);
+}
+
+#[test]
+fn test_sort_conjuncts() {
+    let name_not_null = Expr::IsNotNull(Box::new(col("name")));
+    let predicate = binary(
+        binary(salary_gt(), BinaryOperator::And, name_not_null.clone()),
+        BinaryOperator::And,
+        id_lt(),
+    );
+    let result = OptimizationRule::sort_conjuncts(&filter(predicate, scan("employees"))).unwrap();
+    assert!(result.was_transformed());
+    let expected = binary(
+        binary(id_lt(), BinaryOperator::And, name_not_null),
+        BinaryOperator::And,
+        salary_gt(),
+    );
+    assert_eq!(result.into_inner(), filter(expected, scan("employees")));
+
+    // Already in order
+    let plan = filter(combined_predicate(), scan("employees"));
+    let result = OptimizationRule::sort_conjuncts(&plan).unwrap();
+    assert!(!result.was_transformed());
+    assert_eq!(result.into_inner(), plan);
+}
+
+#[test]
+fn test_sort_conjuncts_keeps_guards() {
+    // Sorting would divide by zero before checking for it
+    let id_not_zero = binary(col("id"), BinaryOperator::Ne, lit(0));
+    let ratio_gt = binary(
+        binary(lit(10), BinaryOperator::Divide, col("id")),
+        BinaryOperator::Gt,
+        lit(1),
+    );
+    let predicate = binary(id_not_zero.clone(), BinaryOperator::And, ratio_gt.clone());
+    let plan = filter(predicate, scan("employees"));
+    let result = OptimizationRule::sort_conjuncts(&plan).unwrap();
+    assert!(!result.was_transformed());
+    assert_eq!(result.into_inner(), plan);
+
+    // Conjuncts on either side of the division are sorted among themselves
+    let predicate = binary(
+        binary(
+            binary(salary_gt(), BinaryOperator::And, id_lt()),
+            BinaryOperator::And,
+            ratio_gt.clone(),
+        ),
+        BinaryOperator::And,
+        id_not_zero.clone(),
+    );
+    let result = OptimizationRule::sort_conjuncts(&filter(predicate, scan("employees"))).unwrap();
+    let expected = binary(
+        binary(
+            binary(id_lt(), BinaryOperator::And, salary_gt()),
+            BinaryOperator::And,
+            ratio_gt,
+        ),
+        BinaryOperator::And,
+        id_not_zero,
+    );
+    assert_eq!(result.into_inner(), filter(expected, scan("employees")));
+}
+
+#[test]
+fn test_combine_filters_plan_shape() {
+    let result = example_plan()
+        .transform(OptimizationRule::combine_filters)
+        .unwrap();
+    assert_plan_shape(&result.into_inner(), "Limit[Projection[Filter[TableScan]]]");
+    assert_plan_shape(
+        &example_plan(),
+        "Limit[Projection[Filter[Filter[TableScan]]]]",
+    );
+}
+
+#[test]
+#[should_panic(expected = "plan shape mismatch")]
+fn test_plan_shape_mismatch() {
+    assert_plan_shape(&example_plan(), "Limit[Filter[TableScan]]");
+}
+
+#[test]
+fn test_transform_tracked() {
+    let (result, paths) = example_plan()
+        .transform_tracked(OptimizationRule::combine_filters)
+        .unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner(),
+        limit(
+            10,
+            projection(
+                employee_columns(),
+                filter(combined_predicate(), scan("employees"))
+            )
+        )
+    );
+    // Limit -> Projection -> outer Filter
+    assert_eq!(paths, vec![vec![0, 0]]);
+
+    let (result, paths) = scan("employees")
+        .transform_tracked(OptimizationRule::combine_filters)
+        .unwrap();
+    assert!(!result.was_transformed());
+    assert!(paths.is_empty());
+}
+
+#[test]
+fn test_merge_projections_through_alias() {
+    let total = binary(col("salary"), BinaryOperator::Plus, col("id"));
+    let lower = projection(
+        vec![alias(total.clone(), "x"), col("name")],
+        scan("employees"),
+    );
+    let plan = projection(
+        vec![binary(col("x"), BinaryOperator::Plus, lit(1)), col("name")],
+        lower,
+    );
+
+    let result = OptimizationRule::merge_projections(&plan).unwrap();
+    assert!(result.was_transformed());
+    let expected = projection(
+        vec![
+            alias(binary(total, BinaryOperator::Plus, lit(1)), "x + 1"),
+            col("name"),
+        ],
+        scan("employees"),
+    );
+    let merged = result.into_inner();
+    assert_eq!(merged, expected);
+    assert_eq!(merged.output_names().unwrap(), plan.output_names().unwrap());
+}
+
+#[test]
+fn test_merge_projections_does_not_duplicate_computation() {
+    let total = binary(col("salary"), BinaryOperator::Plus, col("id"));
+    let lower = projection(vec![alias(total, "x")], scan("employees"));
+    let plan = projection(
+        vec![binary(col("x"), BinaryOperator::Multiply, col("x"))],
+        lower,
+    );
+
+    let result = OptimizationRule::merge_projections(&plan).unwrap();
+    assert!(!result.was_transformed());
+    assert_eq!(result.into_inner(), plan);
+}
+
+#[test]
+fn test_pull_equi_filter_into_inner_join() {
+    // b.name = a.name AND a.salary > 50000
+    let predicate = binary(
+        binary(
+            qualified_col("b", "name"),
+            BinaryOperator::Eq,
+            qualified_col("a", "name"),
+        ),
+        BinaryOperator::And,
+        binary(qualified_col("a", "salary"), BinaryOperator::Gt, lit(50000)),
+    );
+    let plan = filter(predicate, join(scan("a"), scan("b"), JoinType::Inner));
+
+    let result = OptimizationRule::pull_equi_filter_into_join(&plan).unwrap();
+    assert!(result.was_transformed());
+    let expected = filter(
+        binary(qualified_col("a", "salary"), BinaryOperator::Gt, lit(50000)),
+        LogicalPlan::Join(Join {
+            left: Arc::new(scan("a")),
+            right: Arc::new(scan("b")),
+            on: vec![
+                ("id".to_string(), "id".to_string()),
+                ("name".to_string(), "name".to_string()),
+            ],
+            join_type: JoinType::Inner,
+        }),
+    );
+    assert_eq!(result.into_inner(), expected);
+}
+
+#[test]
+fn test_pull_equi_filter_keeps_outer_join_filter() {
+    let predicate = binary(
+        qualified_col("a", "name"),
+        BinaryOperator::Eq,
+        qualified_col("b", "name"),
+    );
+    let plan = filter(predicate, join(scan("a"), scan("b"), JoinType::Left));
+
+    let result = OptimizationRule::pull_equi_filter_into_join(&plan).unwrap();
+    assert!(!result.was_transformed());
+    assert_eq!(result.into_inner(), plan);
+}
+
+#[test]
+fn test_propagate_join_predicates_from_join_inputs() {
+    let id_gt = |table| binary(qualified_col(table, "id"), BinaryOperator::Gt, lit(5));
+    let salary_gt = binary(qualified_col("a", "salary"), BinaryOperator::Gt, lit(100));
+    let plan = join(
+        filter(
+            binary(id_gt("a"), BinaryOperator::And, salary_gt.clone()),
+            scan("a"),
+        ),
+        scan("b"),
+        JoinType::Inner,
+    );
+    let result = OptimizationRule::propagate_join_predicates(&plan).unwrap();
+    assert!(result.was_transformed());
+    // Only the predicate on the join key carries over
+    let expected = join(
+        filter(
+            binary(id_gt("a"), BinaryOperator::And, salary_gt),
+            scan("a"),
+        ),
+        filter(id_gt("b"), scan("b")),
+        JoinType::Inner,
+    );
+    let optimized = result.into_inner();
+    assert_eq!(optimized, expected);
+
+    // Both sides now agree, so there is nothing more to derive
+    let result = OptimizationRule::propagate_join_predicates(&optimized).unwrap();
+    assert!(!result.was_transformed());
+
+    // Outer joins keep unmatched rows of the other side
+    let LogicalPlan::Join(outer) = plan else {
+        unreachable!()
+    };
+    let outer = LogicalPlan::Join(Join {
+        join_type: JoinType::Left,
+        ..outer
+    });
+    let result = OptimizationRule::propagate_join_predicates(&outer).unwrap();
+    assert!(!result.was_transformed());
+}
+
+#[test]
+fn test_propagate_join_predicates() {
+    let id_gt = |table| binary(qualified_col(table, "id"), BinaryOperator::Gt, lit(5));
+    let plan = filter(id_gt("a"), join(scan("a"), scan("b"), JoinType::Inner));
+    let result = OptimizationRule::propagate_join_predicates(&plan).unwrap();
+    assert!(result.was_transformed());
+    let optimized = result.into_inner();
+    assert_eq!(
+        optimized,
+        filter(
+            id_gt("a"),
+            join(scan("a"), filter(id_gt("b"), scan("b")), JoinType::Inner)
+        )
+    );
+    // The derived predicate isn't derived again
+    let result = OptimizationRule::propagate_join_predicates(&optimized).unwrap();
+    assert!(!result.was_transformed());
+}
+
+#[test]
+fn test_propagate_join_predicates_needs_equi_key() {
+    // salary is only related across the join by a non-equi condition
+    let salary = |table| qualified_col(table, "salary");
+    let predicate = binary(
+        binary(salary("a"), BinaryOperator::Lt, salary("b")),
+        BinaryOperator::And,
+        binary(salary("a"), BinaryOperator::Gt, lit(5)),
+    );
+    let plan = filter(predicate, join(scan("a"), scan("b"), JoinType::Inner));
+    let result = OptimizationRule::propagate_join_predicates(&plan).unwrap();
+    assert!(!result.was_transformed());
+}
+
+#[test]
+fn test_propagate_join_predicates_skips_failing_conjuncts() {
+    // On b, (10 / b.id) > 1 would also divide by the ids that never match
+    let predicate = binary(
+        binary(lit(10), BinaryOperator::Divide, qualified_col("a", "id")),
+        BinaryOperator::Gt,
+        lit(1),
+    );
+    let plan = filter(predicate, join(scan("a"), scan("b"), JoinType::Inner));
+    let result = OptimizationRule::propagate_join_predicates(&plan).unwrap();
+    assert!(!result.was_transformed());
+
+    // Nor is one from the filter of an input
+    let predicate = binary(
+        binary(lit(10), BinaryOperator::Divide, qualified_col("a", "id")),
+        BinaryOperator::Gt,
+        lit(1),
+    );
+    let plan = join(filter(predicate, scan("a")), scan("b"), JoinType::Inner);
+    let result = OptimizationRule::propagate_join_predicates(&plan).unwrap();
+    assert!(!result.was_transformed());
+}
+
+#[test]
+fn test_align_projection_order() {
+    let plan = projection(vec![col("id"), col("name")], scan("employees"));
+    let result = OptimizationRule::align_projection_order(&plan, &["name", "id"]).unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner(),
+        projection(vec![col("name"), col("id")], scan("employees"))
+    );
+
+    // A plan without a projection gets one
+    let result =
+        OptimizationRule::align_projection_order(&scan("employees"), &["salary", "name", "id"]);
+    let aligned = result.unwrap().into_inner();
+    assert_eq!(aligned.output_names().unwrap(), ["salary", "name", "id"]);
+    assert!(matches!(aligned, LogicalPlan::Projection(_)));
+
+    let result = OptimizationRule::align_projection_order(&plan, &["id", "name"]).unwrap();
+    assert!(!result.was_transformed());
+    assert!(OptimizationRule::align_projection_order(&plan, &["name"]).is_err());
+}
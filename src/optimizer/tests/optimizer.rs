@@ -0,0 +1,348 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::analyzer::Analyzer;
+use common::error::{Error, Result};
+use common::expr::{BinaryOperator, Expr};
+use common::plan::{Filter, JoinType, Limit, LogicalPlan, Projection, TableScan};
+use common::scalar::ScalarValue;
+use common::schema::{DataType, Field, Schema};
+use common::testing::{binary, col, filter, join, lit, qualified_col};
+use common::tree_node::Transformed;
+use optimizer::optimizer::Optimizer;
+use optimizer::rules::OptimizationRule;
+
+fn employees_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("salary", DataType::Int64, true),
+    ])
+}
+
+fn scan(table_name: &str) -> LogicalPlan {
+    LogicalPlan::TableScan(TableScan {
+        table_name: table_name.to_string(),
+        table_schema: employees_schema(),
+        projected_columns: vec!["id".to_string(), "name".to_string(), "salary".to_string()],
+    })
+}
+
+fn projection(input: LogicalPlan) -> LogicalPlan {
+    LogicalPlan::Projection(Projection {
+        expr: vec![col("id"), col("name"), col("salary")],
+        input: Arc::new(input),
+    })
+}
+
+fn limit(input: LogicalPlan) -> LogicalPlan {
+    LogicalPlan::Limit(Limit {
+        fetch: 10,
+        input: Arc::new(input),
+    })
+}
+
+fn salary_gt() -> Expr {
+    binary(col("salary"), BinaryOperator::Gt, lit(50000))
+}
+
+fn id_lt() -> Expr {
+    binary(col("id"), BinaryOperator::Lt, lit(1000))
+}
+
+fn combined_predicate() -> Expr {
+    binary(id_lt(), BinaryOperator::And, salary_gt())
+}
+
+/// Filter(id < 1000) -> Filter(salary > 50000) -> TableScan
+fn nested_filters(table_name: &str) -> LogicalPlan {
+    filter(id_lt(), filter(salary_gt(), scan(table_name)))
+}
+
+/// A rule that reads every column of the table, adding columns to the
+/// output of scans that only read some of them.
+fn widen_scans(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+    match plan {
+        LogicalPlan::TableScan(scan) if scan.projected_columns.len() < 3 => {
+            Ok(Transformed::yes(LogicalPlan::TableScan(TableScan {
+                projected_columns: vec!["id".to_string(), "name".to_string(), "salary".to_string()],
+                ..scan.clone()
+            })))
+        }
+        _ => Ok(Transformed::no(plan.clone())),
+    }
+}
+
+/// A rule that fails on every filter.
+fn reject_filters(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+    match plan {
+        LogicalPlan::Filter(_) => Err(Error::Plan("filters are not supported".to_string())),
+        _ => Ok(Transformed::no(plan.clone())),
+    }
+}
+
+/// A rule that changes nothing, slowly.
+fn slow_rule(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+    std::thread::sleep(Duration::from_millis(5));
+    Ok(Transformed::no(plan.clone()))
+}
+
+/// A broken rule that filters by a number instead of a condition.
+fn corrupt_filters(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+    match plan {
+        LogicalPlan::Filter(Filter { predicate, input }) if *predicate != col("salary") => Ok(
+            Transformed::yes(filter(col("salary"), input.as_ref().clone())),
+        ),
+        _ => Ok(Transformed::no(plan.clone())),
+    }
+}
+
+/// A broken pruning rule that drops the last column of every scan.
+fn prune_last_column(plan: &LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+    match plan {
+        LogicalPlan::TableScan(scan) if scan.projected_columns.len() > 1 => {
+            let mut projected_columns = scan.projected_columns.clone();
+            projected_columns.pop();
+            Ok(Transformed::yes(LogicalPlan::TableScan(TableScan {
+                projected_columns,
+                ..scan.clone()
+            })))
+        }
+        _ => Ok(Transformed::no(plan.clone())),
+    }
+}
+
+#[test]
+fn test_optimize_example_plan() {
+    let plan = limit(projection(nested_filters("employees")));
+    let optimized = Optimizer::new().optimize(&plan).unwrap();
+    assert_eq!(
+        optimized,
+        projection(limit(filter(combined_predicate(), scan("employees"))))
+    );
+}
+
+#[test]
+fn test_reoptimize_subtree_leaves_siblings() {
+    let plan = join(nested_filters("a"), nested_filters("b"), JoinType::Inner);
+    let optimized = Optimizer::new().reoptimize_subtree(&plan, &[1]).unwrap();
+    assert_eq!(
+        optimized,
+        join(
+            nested_filters("a"),
+            filter(combined_predicate(), scan("b")),
+            JoinType::Inner
+        )
+    );
+}
+
+#[test]
+fn test_reoptimize_subtree_invalid_path() {
+    let plan = join(nested_filters("a"), nested_filters("b"), JoinType::Inner);
+    let result = Optimizer::new().reoptimize_subtree(&plan, &[0, 0, 0, 0]);
+    assert!(matches!(result, Err(Error::Plan(_))));
+}
+
+#[test]
+fn test_optimize_error_path() {
+    let optimizer = Optimizer::with_rules(vec![("reject_filters", reject_filters)]);
+    // The deepest filter fails first
+    let error = optimizer
+        .optimize(&limit(projection(nested_filters("employees"))))
+        .unwrap_err();
+    assert_eq!(error.path(), Some(&[0, 0, 0][..]));
+    assert!(matches!(error.inner(), Error::Plan(_)));
+    assert_eq!(
+        error.to_string(),
+        "Error during planning: filters are not supported at Filter (root/0/0/0)"
+    );
+
+    // Paths are from the root of the whole plan, not of the subtree
+    let plan = join(nested_filters("a"), nested_filters("b"), JoinType::Inner);
+    let error = optimizer.reoptimize_subtree(&plan, &[1]).unwrap_err();
+    assert_eq!(error.path(), Some(&[1, 0][..]));
+}
+
+#[test]
+fn test_reoptimize_subtree_rejects_schema_change() {
+    let narrow_scan = LogicalPlan::TableScan(TableScan {
+        table_name: "a".to_string(),
+        table_schema: employees_schema(),
+        projected_columns: vec!["id".to_string()],
+    });
+    let plan = join(filter(id_lt(), narrow_scan), scan("b"), JoinType::Inner);
+    let optimizer = Optimizer::with_rules(vec![("widen_scans", widen_scans)]);
+    let error = optimizer.reoptimize_subtree(&plan, &[0]).unwrap_err();
+    assert!(matches!(error.inner(), Error::Plan(_)));
+    assert_eq!(error.path(), Some(&[0][..]));
+}
+
+#[test]
+fn test_optimize_orders_conjuncts_deterministically() {
+    // The same filters, combined in opposite orders
+    let plans = [
+        filter(id_lt(), filter(salary_gt(), scan("employees"))),
+        filter(salary_gt(), filter(id_lt(), scan("employees"))),
+    ];
+    let optimizer = Optimizer::new();
+    let [first, second] = plans.map(|plan| optimizer.optimize(&plan).unwrap());
+    assert_eq!(first, second);
+    let LogicalPlan::Filter(Filter { predicate, .. }) = first else {
+        panic!("expected a filter");
+    };
+    assert_eq!(predicate.to_string(), "(id < 1000) AND (salary > 50000)");
+}
+
+#[test]
+fn test_optimize_with_stats() {
+    let plan = limit(projection(nested_filters("employees")));
+    let optimizer = Optimizer::new();
+    let (optimized, stats) = optimizer.optimize_with_stats(&plan).unwrap();
+    assert_eq!(optimized, optimizer.optimize(&plan).unwrap());
+
+    let combine_filters = &stats.per_rule["combine_filters"];
+    assert_eq!(combine_filters.fires, 1);
+    // Five nodes in the first pass, four once the filters are combined
+    assert_eq!(combine_filters.invocations, 9);
+    assert_eq!(stats.per_rule["push_down_limit"].fires, 1);
+    assert_eq!(stats.per_rule["remove_redundant_cast"].fires, 0);
+    assert_eq!(stats.per_rule.len(), optimizer.rules.len());
+}
+
+#[test]
+fn test_optimize_explained() {
+    let plan = limit(projection(nested_filters("employees")));
+    let optimizer = Optimizer::new();
+    let (optimized, snapshots) = optimizer.optimize_explained(&plan).unwrap();
+    assert_eq!(optimized, optimizer.optimize(&plan).unwrap());
+
+    let schema = "[id:Int64, name:Utf8?, salary:Int64?]";
+    let steps: Vec<_> = snapshots.iter().map(|(rule, _)| rule.as_str()).collect();
+    assert_eq!(steps, ["combine_filters", "push_down_limit"]);
+    assert_eq!(
+        snapshots[0].1,
+        format!(
+            "Limit: fetch=10 {schema}\n  \
+             Projection: id, name, salary {schema}\n    \
+             Filter: (id < 1000) AND (salary > 50000) {schema}\n      \
+             TableScan: employees {schema}\n"
+        )
+    );
+    assert_eq!(snapshots[1].1, optimized.display_with_schema());
+}
+
+#[test]
+fn test_optimize_stops_after_max_duration() {
+    let plan = nested_filters("employees");
+    let optimizer = Optimizer::with_rules(vec![
+        ("slow_rule", slow_rule),
+        ("combine_filters", OptimizationRule::combine_filters),
+    ])
+    .with_max_duration(Duration::from_millis(1));
+    let (optimized, stats) = optimizer.optimize_with_stats(&plan).unwrap();
+    // Stopped before combining the filters
+    assert_eq!(optimized, plan);
+    assert!(!stats.per_rule.contains_key("combine_filters"));
+    assert_eq!(stats.warnings.len(), 1);
+    assert!(
+        stats.warnings[0].contains("before combine_filters"),
+        "{:?}",
+        stats.warnings
+    );
+
+    let (_, stats) = Optimizer::new().optimize_with_stats(&plan).unwrap();
+    assert!(stats.warnings.is_empty());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn test_optimizer_rejects_inconsistent_plans() {
+    let optimizer = Optimizer::with_rules(vec![("corrupt_filters", corrupt_filters)]);
+    let error = optimizer
+        .optimize(&filter(salary_gt(), scan("employees")))
+        .unwrap_err();
+    assert!(matches!(error.inner(), Error::Internal(_)));
+    assert_eq!(error.path(), Some(&[][..]));
+    assert!(
+        error
+            .inner()
+            .to_string()
+            .starts_with("Internal error: rule corrupt_filters left the plan inconsistent"),
+        "{error}"
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn test_optimizer_rejects_dropped_columns() {
+    let optimizer = Optimizer::with_rules(vec![("prune_last_column", prune_last_column)]);
+    let error = optimizer.optimize(&limit(scan("employees"))).unwrap_err();
+    assert_eq!(error.path(), Some(&[][..]));
+    let Error::Internal(message) = error.inner() else {
+        panic!("expected an internal error, got {error:?}");
+    };
+    assert!(message.contains("prune_last_column"), "{message}");
+    assert!(message.contains("salary"), "{message}");
+}
+
+#[test]
+fn test_analyze_before_optimize() {
+    let predicate = binary(
+        col("SALARY"),
+        BinaryOperator::Gt,
+        Expr::Literal(ScalarValue::Int32(Some(50000))),
+    );
+    let plan = limit(filter(predicate, scan("employees")));
+
+    let analyzed = Analyzer::new().analyze(&plan).unwrap();
+    let optimized = Optimizer::new().optimize(&analyzed).unwrap();
+    assert_eq!(
+        optimized.display_with_schema(),
+        "Limit: fetch=10 [id:Int64, name:Utf8?, salary:Int64?]\n  \
+         Filter: employees.salary > CAST(50000 AS Int64) [id:Int64, name:Utf8?, salary:Int64?]\n    \
+         TableScan: employees [id:Int64, name:Utf8?, salary:Int64?]\n"
+    );
+}
+
+#[test]
+fn test_optimize_join_with_computed_projection() {
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![
+            qualified_col("a", "id"),
+            Expr::Coalesce(vec![qualified_col("b", "salary"), lit(0)]),
+        ],
+        input: Arc::new(join(scan("a"), scan("b"), JoinType::Inner)),
+    });
+
+    let optimized = Optimizer::new().optimize(&plan).unwrap();
+    optimized.validate().unwrap();
+    assert_eq!(optimized.schema().unwrap(), plan.schema().unwrap());
+    // The computed column is evaluated below the join, and kept
+    assert_eq!(
+        optimized.display_with_schema(),
+        "Projection: a.id, coalesce(b.salary, 0) [id:Int64, coalesce(b.salary, 0):Int64]\n  \
+         Inner Join: id = id [id:Int64, name:Utf8?, salary:Int64?, id:Int64, name:Utf8?, \
+         salary:Int64?, coalesce(b.salary, 0):Int64]\n    \
+         TableScan: a [id:Int64, name:Utf8?, salary:Int64?]\n    \
+         Projection: b.id, b.name, b.salary, coalesce(b.salary, 0) AS coalesce(b.salary, 0) \
+         [id:Int64, name:Utf8?, salary:Int64?, coalesce(b.salary, 0):Int64]\n      \
+         TableScan: b [id:Int64, name:Utf8?, salary:Int64?]\n"
+    );
+}
+
+#[test]
+fn test_optimize_keeps_division_guard() {
+    let id_not_zero = binary(col("id"), BinaryOperator::Ne, lit(0));
+    let ratio_gt = binary(
+        binary(lit(10), BinaryOperator::Divide, col("id")),
+        BinaryOperator::Gt,
+        lit(1),
+    );
+    let predicate = binary(id_not_zero, BinaryOperator::And, ratio_gt);
+    let plan = filter(predicate, scan("employees"));
+    let optimized = Optimizer::new().optimize(&plan).unwrap();
+    let LogicalPlan::Filter(Filter { predicate, .. }) = optimized else {
+        panic!("expected a filter");
+    };
+    assert_eq!(predicate.to_string(), "(id != 0) AND ((10 / id) > 1)");
+}
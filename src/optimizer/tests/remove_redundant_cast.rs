@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use common::expr::{Alias, Cast, Expr};
+use common::plan::{LogicalPlan, Projection, TableScan};
+use common::schema::{DataType, Field, Schema};
+use common::testing::col;
+use optimizer::rules::OptimizationRule;
+use optimizer::simplify_expressions::remove_redundant_casts;
+
+fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("age", DataType::Int32, true),
+    ])
+}
+
+fn cast(expr: Expr, data_type: DataType) -> Expr {
+    Expr::Cast(Cast {
+        expr: Box::new(expr),
+        data_type,
+    })
+}
+
+fn assert_removes_casts(expr: Expr, expected: Expr) {
+    let result = remove_redundant_casts(&expr, &schema()).unwrap();
+    assert_eq!(result.transformed, expr != expected);
+    assert_eq!(result.into_inner(), expected);
+}
+
+#[test]
+fn test_cast_to_same_type_is_removed() {
+    assert_removes_casts(cast(col("id"), DataType::Int64), col("id"));
+    assert_removes_casts(
+        cast(cast(col("age"), DataType::Int32), DataType::Int64),
+        cast(col("age"), DataType::Int64),
+    );
+}
+
+#[test]
+fn test_lossless_inner_cast_is_collapsed() {
+    assert_removes_casts(
+        cast(cast(col("age"), DataType::Int64), DataType::Float64),
+        cast(col("age"), DataType::Float64),
+    );
+    // Widening and narrowing back is a no-op altogether
+    assert_removes_casts(
+        cast(cast(col("age"), DataType::Int64), DataType::Int32),
+        col("age"),
+    );
+}
+
+#[test]
+fn test_lossy_inner_cast_is_kept() {
+    // Narrowing to Int32 may fail, so it can't be skipped
+    let expr = cast(cast(col("id"), DataType::Int32), DataType::Int64);
+    assert_removes_casts(expr.clone(), expr);
+
+    let expr = cast(cast(col("id"), DataType::Utf8), DataType::Int32);
+    assert_removes_casts(expr.clone(), expr);
+}
+
+#[test]
+fn test_remove_redundant_cast_keeps_projection_names() {
+    let scan = Arc::new(LogicalPlan::TableScan(TableScan {
+        table_name: "people".to_string(),
+        table_schema: schema(),
+        projected_columns: vec!["id".to_string(), "age".to_string()],
+    }));
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![cast(col("id"), DataType::Int64)],
+        input: scan.clone(),
+    });
+
+    let result = OptimizationRule::remove_redundant_cast(&plan).unwrap();
+    assert!(result.transformed);
+    let expected = LogicalPlan::Projection(Projection {
+        expr: vec![Expr::Alias(Alias {
+            expr: Box::new(col("id")),
+            name: "CAST(id AS Int64)".to_string(),
+        })],
+        input: scan,
+    });
+    assert_eq!(result.into_inner(), expected);
+}
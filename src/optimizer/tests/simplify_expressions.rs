@@ -0,0 +1,325 @@
+use std::sync::Arc;
+
+use common::expr::{Alias, BinaryOperator, Case, Cast, Expr};
+use common::plan::{Filter, LogicalPlan, Projection, TableScan};
+use common::scalar::ScalarValue;
+use common::schema::{DataType, Field, Schema};
+use common::testing::{binary, col, lit};
+use common::tree_node::TreeNode;
+use optimizer::rules::OptimizationRule;
+use optimizer::simplify_expressions::simplify;
+
+fn employees_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("salary", DataType::Int64, true),
+        Field::new("bonus", DataType::Int64, true),
+        Field::new("age", DataType::Int32, false),
+        Field::new("rating", DataType::Float64, false),
+    ])
+}
+
+fn lit_bool(value: bool) -> Expr {
+    Expr::Literal(ScalarValue::Boolean(Some(value)))
+}
+
+fn null() -> Expr {
+    Expr::Literal(ScalarValue::Int64(None))
+}
+
+fn gt(left: Expr, right: Expr) -> Expr {
+    binary(left, BinaryOperator::Gt, right)
+}
+
+fn case(when_then: Vec<(Expr, Expr)>, else_expr: Option<Expr>) -> Expr {
+    Expr::Case(Case {
+        expr: None,
+        when_then_expr: when_then
+            .into_iter()
+            .map(|(when, then)| (Box::new(when), Box::new(then)))
+            .collect(),
+        else_expr: else_expr.map(Box::new),
+    })
+}
+
+/// Simplify against the employees schema, asserting whether it changed.
+fn assert_simplifies(expr: Expr, expected: Expr) {
+    let result = simplify(&expr, &employees_schema()).unwrap();
+    assert_eq!(result.transformed, expr != expected);
+    assert_eq!(result.into_inner(), expected);
+}
+
+#[test]
+fn test_case_when_true_collapses_to_then() {
+    assert_simplifies(
+        case(vec![(lit_bool(true), col("name"))], Some(col("id"))),
+        col("name"),
+    );
+}
+
+#[test]
+fn test_case_drops_false_branches() {
+    assert_simplifies(
+        case(
+            vec![
+                (lit_bool(false), lit(1)),
+                (gt(col("salary"), lit(10000)), lit(2)),
+                (Expr::Literal(ScalarValue::Boolean(None)), lit(3)),
+            ],
+            Some(lit(4)),
+        ),
+        case(vec![(gt(col("salary"), lit(10000)), lit(2))], Some(lit(4))),
+    );
+
+    // A true condition is only taken once the false branches before it are gone
+    assert_simplifies(
+        case(
+            vec![(lit_bool(false), lit(1)), (lit_bool(true), lit(2))],
+            None,
+        ),
+        lit(2),
+    );
+}
+
+#[test]
+fn test_case_without_branches_collapses_to_else() {
+    assert_simplifies(
+        case(vec![(lit_bool(false), lit(1))], Some(col("id"))),
+        col("id"),
+    );
+    // Without an ELSE, a NULL of the CASE's type
+    assert_simplifies(
+        case(vec![(lit_bool(false), lit(1))], None),
+        Expr::Literal(ScalarValue::Int64(None)),
+    );
+}
+
+#[test]
+fn test_case_without_constant_conditions_is_unchanged() {
+    let expr = case(vec![(gt(col("salary"), lit(10000)), lit(1))], Some(lit(0)));
+    assert_simplifies(expr.clone(), expr);
+}
+
+#[test]
+fn test_simplify_expressions_rewrites_nested_case_in_filter() {
+    let plan = LogicalPlan::Filter(Filter {
+        predicate: gt(
+            case(vec![(lit_bool(true), col("salary"))], Some(lit(0))),
+            lit(50000),
+        ),
+        input: Arc::new(LogicalPlan::TableScan(TableScan {
+            table_name: "employees".to_string(),
+            table_schema: employees_schema(),
+            projected_columns: vec!["salary".to_string()],
+        })),
+    });
+    let result = plan
+        .transform(OptimizationRule::simplify_expressions)
+        .unwrap();
+    assert!(result.was_transformed());
+    let LogicalPlan::Filter(filter) = result.into_inner() else {
+        panic!("expected a filter");
+    };
+    assert_eq!(filter.predicate, gt(col("salary"), lit(50000)));
+}
+
+#[test]
+fn test_coalesce_drops_args_after_non_null_literal() {
+    assert_simplifies(
+        Expr::Coalesce(vec![col("salary"), lit(0), col("bonus")]),
+        Expr::Coalesce(vec![col("salary"), lit(0)]),
+    );
+}
+
+#[test]
+fn test_coalesce_of_single_arg() {
+    assert_simplifies(Expr::Coalesce(vec![col("salary")]), col("salary"));
+}
+
+#[test]
+fn test_coalesce_drops_null_literals() {
+    assert_simplifies(
+        Expr::Coalesce(vec![col("salary"), null(), col("bonus")]),
+        Expr::Coalesce(vec![col("salary"), col("bonus")]),
+    );
+    assert_simplifies(Expr::Coalesce(vec![null(), col("bonus")]), col("bonus"));
+    assert_simplifies(Expr::Coalesce(vec![null(), null()]), null());
+}
+
+#[test]
+fn test_coalesce_with_non_nullable_first_arg() {
+    assert_simplifies(Expr::Coalesce(vec![col("id"), col("salary")]), col("id"));
+
+    // The dropped Int64 argument widened the result
+    assert_simplifies(
+        Expr::Coalesce(vec![col("age"), col("salary")]),
+        Expr::Cast(Cast {
+            expr: Box::new(col("age")),
+            data_type: DataType::Int64,
+        }),
+    );
+}
+
+#[test]
+fn test_coalesce_without_simplification() {
+    let expr = Expr::Coalesce(vec![col("salary"), col("bonus")]);
+    assert_simplifies(expr.clone(), expr);
+}
+
+#[test]
+fn test_comparison_with_null() {
+    let unknown = Expr::Literal(ScalarValue::Boolean(None));
+    assert_simplifies(
+        binary(col("salary"), BinaryOperator::Eq, null()),
+        unknown.clone(),
+    );
+    assert_simplifies(
+        binary(
+            Expr::Literal(ScalarValue::Utf8(None)),
+            BinaryOperator::Ne,
+            col("name"),
+        ),
+        unknown.clone(),
+    );
+    assert_simplifies(gt(Expr::Literal(ScalarValue::Null), col("salary")), unknown);
+
+    // The folded condition can then be dropped from a CASE
+    assert_simplifies(
+        case(
+            vec![
+                (binary(col("bonus"), BinaryOperator::Eq, null()), lit(1)),
+                (gt(col("salary"), lit(100)), lit(2)),
+            ],
+            None,
+        ),
+        case(vec![(gt(col("salary"), lit(100)), lit(2))], None),
+    );
+
+    let expr = binary(col("salary"), BinaryOperator::Le, col("bonus"));
+    assert_simplifies(expr.clone(), expr);
+}
+
+#[test]
+fn test_additive_identities() {
+    assert_simplifies(
+        binary(col("salary"), BinaryOperator::Plus, lit(0)),
+        col("salary"),
+    );
+    assert_simplifies(
+        binary(lit(0), BinaryOperator::Plus, col("salary")),
+        col("salary"),
+    );
+    assert_simplifies(
+        binary(col("salary"), BinaryOperator::Minus, lit(0)),
+        col("salary"),
+    );
+
+    // 0 - x is not x
+    let expr = binary(lit(0), BinaryOperator::Minus, col("salary"));
+    assert_simplifies(expr.clone(), expr);
+
+    // age + 0 is an Int64, but age is an Int32
+    let expr = binary(col("age"), BinaryOperator::Plus, lit(0));
+    assert_simplifies(expr.clone(), expr);
+}
+
+#[test]
+fn test_multiplicative_identities() {
+    assert_simplifies(
+        binary(col("salary"), BinaryOperator::Multiply, lit(1)),
+        col("salary"),
+    );
+    assert_simplifies(
+        binary(lit(1), BinaryOperator::Multiply, col("salary")),
+        col("salary"),
+    );
+    assert_simplifies(
+        binary(col("salary"), BinaryOperator::Divide, lit(1)),
+        col("salary"),
+    );
+
+    // 1 / x is not x
+    let expr = binary(lit(1), BinaryOperator::Divide, col("salary"));
+    assert_simplifies(expr.clone(), expr);
+}
+
+#[test]
+fn test_multiply_by_zero() {
+    assert_simplifies(binary(col("id"), BinaryOperator::Multiply, lit(0)), lit(0));
+    assert_simplifies(binary(lit(0), BinaryOperator::Multiply, col("id")), lit(0));
+
+    // NULL * 0 is NULL, so a nullable operand must be kept
+    let expr = binary(col("salary"), BinaryOperator::Multiply, lit(0));
+    assert_simplifies(expr.clone(), expr);
+
+    // The zero has the result's type
+    assert_simplifies(binary(col("age"), BinaryOperator::Multiply, lit(0)), lit(0));
+
+    // inf * 0 is NaN
+    let expr = binary(col("rating"), BinaryOperator::Multiply, lit(0));
+    assert_simplifies(expr.clone(), expr);
+}
+
+#[test]
+fn test_simplify_expressions_keeps_projection_names() {
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![
+            binary(col("salary"), BinaryOperator::Plus, lit(0)),
+            binary(col("id"), BinaryOperator::Multiply, lit(1)),
+        ],
+        input: Arc::new(LogicalPlan::TableScan(TableScan {
+            table_name: "employees".to_string(),
+            table_schema: employees_schema(),
+            projected_columns: vec!["id".to_string(), "salary".to_string()],
+        })),
+    });
+    let names = |plan: &LogicalPlan| -> Vec<String> {
+        let schema = plan.schema().unwrap();
+        schema.fields.into_iter().map(|field| field.name).collect()
+    };
+
+    let result = plan
+        .transform(OptimizationRule::simplify_expressions)
+        .unwrap();
+    assert!(result.was_transformed());
+    let simplified = result.into_inner();
+    assert_eq!(names(&simplified), names(&plan));
+
+    let LogicalPlan::Projection(projection) = simplified else {
+        panic!("expected a projection");
+    };
+    assert_eq!(
+        projection.expr[0],
+        Expr::Alias(Alias {
+            expr: Box::new(col("salary")),
+            name: "salary + 0".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_simplify_expressions_keeps_projection_schema() {
+    let plan = LogicalPlan::Projection(Projection {
+        expr: vec![
+            binary(col("age"), BinaryOperator::Plus, lit(0)),
+            binary(col("rating"), BinaryOperator::Multiply, lit(0)),
+            binary(col("age"), BinaryOperator::Multiply, lit(0)),
+            binary(col("id"), BinaryOperator::Plus, lit(0)),
+        ],
+        input: Arc::new(LogicalPlan::TableScan(TableScan {
+            table_name: "employees".to_string(),
+            table_schema: employees_schema(),
+            projected_columns: vec!["id".to_string(), "age".to_string(), "rating".to_string()],
+        })),
+    });
+
+    let result = plan
+        .transform(OptimizationRule::simplify_expressions)
+        .unwrap();
+    assert!(result.was_transformed());
+    assert_eq!(
+        result.into_inner().schema().unwrap(),
+        plan.schema().unwrap()
+    );
+}